@@ -0,0 +1,358 @@
+//! Converts MJML documents into [`view!`](https://docs.rs/mrmx/latest/mrmx/macro.view.html) markup for `mrmx`.
+//!
+//! The `mrmx` binary converts existing templates:
+//!
+//! ```sh
+//! mrmx convert templates/*.mjml --out-dir src/emails
+//! ```
+//!
+//! Each template is written to a `.rs` file holding a `view! { ... }`
+//! expression, which can be used with `include!("emails/welcome.rs")`. Text
+//! that `view!` cannot take unquoted, like text with apostrophes, is written as
+//! a string literal.
+//!
+//! ```
+//! let code = mrmx_cli::convert("<mj-text>It's here</mj-text>").unwrap();
+//! assert_eq!(code, "view! {\n    <mj-text>\"It's here\"</mj-text>\n}\n");
+//! ```
+
+use std::fmt;
+
+use mrml::prelude::parser::memory_loader::MemoryIncludeLoader;
+use mrml::prelude::parser::ParserOptions;
+use mrmx_markup::parse::{self, is_raw_text_element};
+
+/// Lines longer than this are split, one child or attribute per line.
+const MAX_WIDTH: usize = 100;
+
+const INDENT: &str = "    ";
+
+/// An error in the MJML document being converted.
+#[derive(Debug)]
+pub enum Error {
+    /// The markup is malformed.
+    Syntax {
+        /// The line the error was found on, starting at 1.
+        line: usize,
+        /// The column the error was found at, starting at 1.
+        column: usize,
+        message: String,
+    },
+    /// The document is well-formed, but mrml cannot read it.
+    Mjml(mrml::prelude::parser::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Syntax {
+                line,
+                column,
+                message,
+            } => write!(f, "line {line}, column {column}: {message}"),
+            Error::Mjml(err) => write!(f, "invalid MJML: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Converts an MJML document, or a fragment of one, into a `view! { ... }`
+/// expression.
+///
+/// A whole `<mjml>` document is also read with mrml's parser, so that the
+/// conversion fails on documents mrml would reject.
+pub fn convert(source: &str) -> Result<String, Error> {
+    let nodes = parse::parse(source).map_err(|err| {
+        let position = err.span.position(source);
+        Error::Syntax {
+            line: position.line,
+            column: position.column,
+            message: err.message,
+        }
+    })?;
+    if let [parse::Node::Element(root)] = significant(&nodes).as_slice() {
+        if root.tag == "mjml" {
+            check_with_mrml(source, &nodes)?;
+        }
+    }
+    let nodes = normalize(nodes.iter().map(Node::from).collect(), None);
+
+    let mut out = String::from("view! {\n");
+    for node in &nodes {
+        print_node(&mut out, node, 1);
+    }
+    out.push_str("}\n");
+    Ok(out)
+}
+
+/// The nodes that are not comments or whitespace.
+fn significant<'a, 'b>(nodes: &'b [parse::Node<'a>]) -> Vec<&'b parse::Node<'a>> {
+    nodes
+        .iter()
+        .filter(|node| match node {
+            parse::Node::Element(_) => true,
+            parse::Node::Text(text) => !text.value.trim().is_empty(),
+            parse::Node::Comment(_) => false,
+        })
+        .collect()
+}
+
+/// Parses the document with mrml. The converted code keeps `<mj-include>`
+/// tags, so included files are not read, and stand for empty partials.
+fn check_with_mrml(source: &str, nodes: &[parse::Node]) -> Result<(), Error> {
+    let mut includes = Vec::new();
+    include_paths(nodes, &mut includes);
+    let options = ParserOptions {
+        include_loader: Box::new(MemoryIncludeLoader::from(
+            includes
+                .into_iter()
+                .map(|path| (path, ""))
+                .collect::<Vec<_>>(),
+        )),
+    };
+    mrml::parse_with_options(source, &options)
+        .map(|_| ())
+        .map_err(Error::Mjml)
+}
+
+fn include_paths<'a>(nodes: &[parse::Node<'a>], paths: &mut Vec<&'a str>) {
+    for node in nodes {
+        let parse::Node::Element(element) = node else {
+            continue;
+        };
+        if element.tag == "mj-include" {
+            paths.extend(
+                element
+                    .attributes
+                    .iter()
+                    .filter(|attribute| attribute.name == "path")
+                    .filter_map(|attribute| Some(attribute.value?.value)),
+            );
+        }
+        include_paths(&element.children, paths);
+    }
+}
+
+#[derive(Debug)]
+enum Node<'a> {
+    Element {
+        tag: &'a str,
+        attributes: Vec<(&'a str, Option<&'a str>)>,
+        children: Vec<Node<'a>>,
+    },
+    Text(String),
+    Comment(&'a str),
+}
+
+impl<'a> From<&parse::Node<'a>> for Node<'a> {
+    fn from(node: &parse::Node<'a>) -> Self {
+        match node {
+            parse::Node::Element(element) => Node::Element {
+                tag: element.tag,
+                attributes: element
+                    .attributes
+                    .iter()
+                    .map(|attribute| (attribute.name, attribute.value.map(|value| value.value)))
+                    .collect(),
+                children: element.children.iter().map(Node::from).collect(),
+            },
+            parse::Node::Text(text) => Node::Text(text.value.to_string()),
+            parse::Node::Comment(comment) => Node::Comment(comment.value.trim()),
+        }
+    }
+}
+
+/// Whether the text in `<tag>` is kept exactly as written.
+fn is_preformatted(tag: &str) -> bool {
+    is_raw_text_element(tag) || tag == "pre"
+}
+
+/// Whether text in `<tag>` is content, rather than indentation between child
+/// elements.
+fn has_text_content(tag: Option<&str>) -> bool {
+    let Some(tag) = tag else {
+        return false;
+    };
+    if !tag.starts_with("mj") {
+        return true;
+    }
+    // Keep list alphabetized for binary search
+    [
+        "mj-accordion-text",
+        "mj-accordion-title",
+        "mj-button",
+        "mj-html-attribute",
+        "mj-navbar-link",
+        "mj-preview",
+        "mj-raw",
+        "mj-social-element",
+        "mj-table",
+        "mj-text",
+        "mj-title",
+    ]
+    .binary_search(&tag)
+    .is_ok()
+}
+
+/// Collapses whitespace the way HTML renders it, dropping whitespace that is
+/// only indentation.
+fn normalize<'a>(nodes: Vec<Node<'a>>, parent: Option<&str>) -> Vec<Node<'a>> {
+    if parent.is_some_and(is_preformatted) {
+        return nodes;
+    }
+    let text_content = has_text_content(parent);
+    let last = nodes.len().saturating_sub(1);
+    nodes
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, node)| match node {
+            Node::Element {
+                tag,
+                attributes,
+                children,
+            } => Some(Node::Element {
+                tag,
+                attributes,
+                children: normalize(children, Some(tag)),
+            }),
+            Node::Text(text) if !text_content => {
+                let text = text.trim();
+                (!text.is_empty()).then(|| Node::Text(text.to_string()))
+            }
+            Node::Text(text) => {
+                let mut text = collapse_whitespace(&text);
+                if i == 0 {
+                    text = text.trim_start().to_string();
+                }
+                if i == last {
+                    text = text.trim_end().to_string();
+                }
+                (!text.is_empty()).then_some(Node::Text(text))
+            }
+            node => Some(node),
+        })
+        .collect()
+}
+
+/// Replaces each run of whitespace with a single space.
+fn collapse_whitespace(text: &str) -> String {
+    let mut collapsed = String::with_capacity(text.len());
+    let mut space = false;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            space = true;
+            continue;
+        }
+        if space {
+            collapsed.push(' ');
+            space = false;
+        }
+        collapsed.push(c);
+    }
+    if space {
+        collapsed.push(' ');
+    }
+    collapsed
+}
+
+fn print_node(out: &mut String, node: &Node, depth: usize) {
+    let indent = INDENT.repeat(depth);
+    let line = single_line(node);
+    if indent.len() + line.len() <= MAX_WIDTH && !line.contains('\n') {
+        out.push_str(&indent);
+        out.push_str(&line);
+        out.push('\n');
+        return;
+    }
+    let Node::Element {
+        tag,
+        attributes,
+        children,
+    } = node
+    else {
+        out.push_str(&indent);
+        out.push_str(&line);
+        out.push('\n');
+        return;
+    };
+    let open = open_tag(tag, attributes, children.is_empty());
+    if indent.len() + open.len() <= MAX_WIDTH {
+        out.push_str(&indent);
+        out.push_str(&open);
+    } else {
+        out.push_str(&format!("{indent}<{tag}\n"));
+        for attribute in attributes {
+            out.push_str(&format!("{indent}{INDENT}{}\n", print_attribute(attribute)));
+        }
+        out.push_str(&indent);
+        out.push_str(if children.is_empty() { "/>" } else { ">" });
+    }
+    out.push('\n');
+    if children.is_empty() {
+        return;
+    }
+    for child in children {
+        print_node(out, child, depth + 1);
+    }
+    out.push_str(&format!("{indent}</{tag}>\n"));
+}
+
+fn single_line(node: &Node) -> String {
+    match node {
+        Node::Element {
+            tag,
+            attributes,
+            children,
+        } => {
+            let mut line = open_tag(tag, attributes, children.is_empty());
+            if !children.is_empty() {
+                // Text keeps its own spaces, so children are printed next to
+                // each other.
+                for child in children {
+                    line.push_str(&single_line(child));
+                }
+                line.push_str(&format!("</{tag}>"));
+            }
+            line
+        }
+        Node::Text(text) => print_text(text),
+        Node::Comment(comment) => format!("<!-- {comment:?} -->"),
+    }
+}
+
+/// The opening tag of an element, self-closing if it has no children.
+fn open_tag(tag: &str, attributes: &[(&str, Option<&str>)], empty: bool) -> String {
+    let mut open = format!("<{tag}");
+    for attribute in attributes {
+        open.push(' ');
+        open.push_str(&print_attribute(attribute));
+    }
+    open.push_str(if empty { " />" } else { ">" });
+    open
+}
+
+fn print_attribute((name, value): &(&str, Option<&str>)) -> String {
+    match value {
+        Some(value) => format!("{name}={value:?}"),
+        None => name.to_string(),
+    }
+}
+
+/// Prints text unquoted if `view!` reads it back unchanged, and as a string
+/// literal otherwise.
+fn print_text(text: &str) -> String {
+    let bare = text.split(' ').all(|word| {
+        word.starts_with(|c: char| c.is_ascii_alphabetic())
+            && word.chars().all(|c| {
+                c.is_ascii_alphanumeric() || matches!(c, '.' | ',' | '!' | '?' | ':' | ';' | '-')
+            })
+    });
+    if bare {
+        return text.to_string();
+    }
+    if text.contains(['"', '\\', '\n']) && !text.contains("\"#") {
+        return format!("r#\"{text}\"#");
+    }
+    format!("{text:?}")
+}
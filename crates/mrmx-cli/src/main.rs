@@ -0,0 +1,57 @@
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand};
+
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Converts MJML templates into `view!` Rust source.
+    Convert {
+        /// The `.mjml` files to convert.
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        /// Writes a `.rs` file for each template into this directory, instead
+        /// of printing them.
+        #[arg(short, long)]
+        out_dir: Option<PathBuf>,
+    },
+}
+
+fn main() -> ExitCode {
+    let Command::Convert { files, out_dir } = Cli::parse().command;
+    let mut status = ExitCode::SUCCESS;
+    for file in &files {
+        if let Err(err) = convert_file(file, out_dir.as_deref(), files.len() > 1) {
+            eprintln!("error: {}: {err}", file.display());
+            status = ExitCode::FAILURE;
+        }
+    }
+    status
+}
+
+fn convert_file(
+    file: &Path,
+    out_dir: Option<&Path>,
+    label: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let source = std::fs::read_to_string(file)?;
+    let code = mrmx_cli::convert(&source)?;
+    match out_dir {
+        Some(out_dir) => {
+            let name = file.file_stem().ok_or("not a file")?;
+            let out = out_dir.join(name).with_extension("rs");
+            std::fs::create_dir_all(out_dir)?;
+            std::fs::write(&out, code)?;
+        }
+        None if label => print!("// {}\n{code}\n", file.display()),
+        None => print!("{code}"),
+    }
+    Ok(())
+}
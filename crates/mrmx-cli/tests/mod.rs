@@ -0,0 +1,128 @@
+use expect_test::expect;
+use mrmx_cli::convert;
+
+#[test]
+fn document() {
+    let expected = expect![[r##"
+        view! {
+            <mjml>
+                <mj-head>
+                    <mj-title>"It's a title!"</mj-title>
+                    <mj-attributes>
+                        <mj-text padding="0" />
+                        <mj-class name="heading" color="red" />
+                    </mj-attributes>
+                    <mj-style inline="inline">
+                        r#"
+              .link { color: #123456; }
+            "#
+                    </mj-style>
+                </mj-head>
+                <mj-body>
+                    <mj-section>
+                        <mj-column>
+                            <!-- "Greeting" -->
+                            <mj-text mj-class="heading">Hello there</mj-text>
+                            <mj-text>
+                                "Don't miss "
+                                <a href="https://example.com">our sale</a>
+                                ", it ends {{ date }}."
+                                <br />
+                            </mj-text>
+                            <mj-button
+                                background-color="#123456"
+                                color="#ffffff"
+                                font-size="16px"
+                                href="https://example.com/sale"
+                            >
+                                Shop now
+                            </mj-button>
+                        </mj-column>
+                    </mj-section>
+                </mj-body>
+            </mjml>
+        }
+    "##]];
+    let actual = convert(
+        r##"<?xml version="1.0" encoding="UTF-8"?>
+<mjml>
+  <mj-head>
+    <mj-title>It's a title!</mj-title>
+    <mj-attributes>
+      <mj-text padding="0" />
+      <mj-class name="heading" color="red" />
+    </mj-attributes>
+    <mj-style inline="inline">
+      .link { color: #123456; }
+    </mj-style>
+  </mj-head>
+  <mj-body>
+    <mj-section>
+      <mj-column>
+        <!-- Greeting -->
+        <mj-text mj-class="heading">
+          Hello there
+        </mj-text>
+        <mj-text>
+          Don't miss <a href="https://example.com">our sale</a>,
+          it ends {{ date }}.<br>
+        </mj-text>
+        <mj-button background-color="#123456" color="#ffffff" font-size="16px" href="https://example.com/sale">Shop now</mj-button>
+      </mj-column>
+    </mj-section>
+  </mj-body>
+</mjml>
+"##,
+    );
+    expected.assert_eq(&actual.unwrap())
+}
+
+#[test]
+fn errors() {
+    let expected = expect![[r#"
+        Err(
+            Syntax {
+                line: 3,
+                column: 3,
+                message: "expected </mj-column>, found </mj-section>",
+            },
+        )
+    "#]];
+    let actual = convert("<mj-section>\n  <mj-column>\n  </mj-section>");
+    expected.assert_debug_eq(&actual)
+}
+
+#[test]
+fn error_on_unindented_closing_tag() {
+    let expected = expect![[r#"
+        Err(
+            Syntax {
+                line: 3,
+                column: 1,
+                message: "expected </mj-column>, found </mj-section>",
+            },
+        )
+    "#]];
+    let actual = convert("<mj-section>\n<mj-column>\n</mj-section>");
+    expected.assert_debug_eq(&actual)
+}
+
+#[test]
+fn inline_children() {
+    let expected = expect![[r#"
+        view! {
+            <mj-text>a<!-- "x" -->b<br />c</mj-text>
+        }
+    "#]];
+    let actual = convert("<mj-text>a<!-- x -->b<br/>c</mj-text>");
+    expected.assert_eq(&actual.unwrap())
+}
+
+#[test]
+fn invalid_mjml() {
+    let actual = convert("<mjml><mj-head><mj-text>Hi</mj-text></mj-head></mjml>");
+    assert!(
+        matches!(actual, Err(mrmx_cli::Error::Mjml(_))),
+        "{actual:?}"
+    );
+}
@@ -0,0 +1,510 @@
+use quote::ToTokens;
+use rstml::node::KeyedAttribute;
+use syn::spanned::Spanned;
+use syn::{Expr, ExprLit, Lit};
+
+use crate::diagnostic::MarkupError;
+use crate::suggest::did_you_mean;
+
+/// Attributes accepted by every MJML body element.
+const GLOBAL: &[&str] = &["css-class", "mj-class"];
+
+const PADDING: &[&str] = &[
+    "padding",
+    "padding-bottom",
+    "padding-left",
+    "padding-right",
+    "padding-top",
+];
+
+const BORDER: &[&str] = &[
+    "border",
+    "border-bottom",
+    "border-left",
+    "border-radius",
+    "border-right",
+    "border-top",
+];
+
+const ICON: &[&str] = &[
+    "icon-align",
+    "icon-height",
+    "icon-position",
+    "icon-unwrapped-alt",
+    "icon-unwrapped-url",
+    "icon-width",
+    "icon-wrapped-alt",
+    "icon-wrapped-url",
+];
+
+const SECTION: &[&[&str]] = &[
+    PADDING,
+    BORDER,
+    &[
+        "background-color",
+        "background-position",
+        "background-position-x",
+        "background-position-y",
+        "background-repeat",
+        "background-size",
+        "background-url",
+        "direction",
+        "full-width",
+        "text-align",
+    ],
+];
+
+/// The attributes of an MJML element, as listed in the MJML documentation.
+/// `<mj-include>` checks its own attributes.
+fn element_attributes(tag: &str) -> Option<&'static [&'static [&'static str]]> {
+    Some(match tag {
+        "mj-accordion" => &[
+            PADDING,
+            ICON,
+            &["border", "container-background-color", "font-family"],
+        ],
+        "mj-accordion-element" => &[ICON, &["background-color", "border", "font-family"]],
+        "mj-accordion-text" => &[
+            PADDING,
+            &[
+                "background-color",
+                "color",
+                "font-family",
+                "font-size",
+                "font-weight",
+                "letter-spacing",
+                "line-height",
+            ],
+        ],
+        "mj-accordion-title" => &[
+            PADDING,
+            &["background-color", "color", "font-family", "font-size"],
+        ],
+        "mj-body" => &[&["background-color", "width"]],
+        "mj-breakpoint" => &[&["width"]],
+        "mj-button" => &[
+            PADDING,
+            BORDER,
+            &[
+                "align",
+                "background-color",
+                "color",
+                "container-background-color",
+                "font-family",
+                "font-size",
+                "font-style",
+                "font-weight",
+                "height",
+                "href",
+                "inner-padding",
+                "letter-spacing",
+                "line-height",
+                "name",
+                "rel",
+                "target",
+                "text-align",
+                "text-decoration",
+                "text-transform",
+                "title",
+                "vertical-align",
+                "width",
+            ],
+        ],
+        "mj-carousel" => &[
+            PADDING,
+            &[
+                "align",
+                "border-radius",
+                "container-background-color",
+                "icon-width",
+                "left-icon",
+                "right-icon",
+                "tb-border",
+                "tb-border-radius",
+                "tb-hover-border-color",
+                "tb-selected-border-color",
+                "tb-width",
+                "thumbnails",
+            ],
+        ],
+        "mj-carousel-image" => &[&[
+            "alt",
+            "border-radius",
+            "href",
+            "rel",
+            "src",
+            "target",
+            "tb-border",
+            "tb-border-radius",
+            "thumbnails-src",
+            "title",
+        ]],
+        "mj-column" => &[
+            PADDING,
+            BORDER,
+            &[
+                "background-color",
+                "direction",
+                "inner-background-color",
+                "inner-border",
+                "inner-border-bottom",
+                "inner-border-left",
+                "inner-border-radius",
+                "inner-border-right",
+                "inner-border-top",
+                "vertical-align",
+                "width",
+            ],
+        ],
+        "mj-divider" => &[
+            PADDING,
+            &[
+                "align",
+                "border-color",
+                "border-style",
+                "border-width",
+                "container-background-color",
+                "width",
+            ],
+        ],
+        "mj-font" => &[&["href", "name"]],
+        "mj-group" => &[&["background-color", "direction", "vertical-align", "width"]],
+        "mj-hero" => &[
+            PADDING,
+            &[
+                "background-color",
+                "background-height",
+                "background-position",
+                "background-url",
+                "background-width",
+                "border-radius",
+                "container-background-color",
+                "height",
+                "inner-background-color",
+                "inner-padding",
+                "inner-padding-bottom",
+                "inner-padding-left",
+                "inner-padding-right",
+                "inner-padding-top",
+                "mode",
+                "vertical-align",
+            ],
+        ],
+        "mj-image" => &[
+            PADDING,
+            BORDER,
+            &[
+                "align",
+                "alt",
+                "container-background-color",
+                "fluid-on-mobile",
+                "font-size",
+                "height",
+                "href",
+                "max-height",
+                "name",
+                "rel",
+                "sizes",
+                "src",
+                "srcset",
+                "target",
+                "title",
+                "usemap",
+                "width",
+            ],
+        ],
+        "mj-navbar" => &[&[
+            "align",
+            "base-url",
+            "hamburger",
+            "ico-align",
+            "ico-close",
+            "ico-color",
+            "ico-font-family",
+            "ico-font-size",
+            "ico-line-height",
+            "ico-open",
+            "ico-padding",
+            "ico-padding-bottom",
+            "ico-padding-left",
+            "ico-padding-right",
+            "ico-padding-top",
+            "ico-text-decoration",
+            "ico-text-transform",
+        ]],
+        "mj-navbar-link" => &[
+            PADDING,
+            &[
+                "color",
+                "font-family",
+                "font-size",
+                "font-style",
+                "font-weight",
+                "href",
+                "letter-spacing",
+                "line-height",
+                "name",
+                "rel",
+                "target",
+                "text-decoration",
+                "text-transform",
+            ],
+        ],
+        "mj-section" | "mj-wrapper" => SECTION,
+        "mj-social" => &[
+            PADDING,
+            &[
+                "align",
+                "border-radius",
+                "color",
+                "container-background-color",
+                "font-family",
+                "font-size",
+                "font-style",
+                "font-weight",
+                "icon-height",
+                "icon-padding",
+                "icon-size",
+                "inner-padding",
+                "line-height",
+                "mode",
+                "table-layout",
+                "text-decoration",
+                "text-padding",
+            ],
+        ],
+        "mj-social-element" => &[
+            PADDING,
+            &[
+                "align",
+                "alt",
+                "background-color",
+                "border-radius",
+                "color",
+                "font-family",
+                "font-size",
+                "font-style",
+                "font-weight",
+                "href",
+                "icon-height",
+                "icon-padding",
+                "icon-position",
+                "icon-size",
+                "line-height",
+                "name",
+                "rel",
+                "sizes",
+                "src",
+                "srcset",
+                "target",
+                "text-decoration",
+                "text-padding",
+                "title",
+                "vertical-align",
+            ],
+        ],
+        "mj-spacer" => &[PADDING, &["container-background-color", "height"]],
+        "mj-style" => &[&["inline"]],
+        "mj-table" => &[
+            PADDING,
+            &[
+                "align",
+                "border",
+                "cellpadding",
+                "cellspacing",
+                "color",
+                "container-background-color",
+                "font-family",
+                "font-size",
+                "line-height",
+                "role",
+                "table-layout",
+                "width",
+            ],
+        ],
+        "mj-text" => &[
+            PADDING,
+            &[
+                "align",
+                "color",
+                "container-background-color",
+                "font-family",
+                "font-size",
+                "font-style",
+                "font-weight",
+                "height",
+                "letter-spacing",
+                "line-height",
+                "text-decoration",
+                "text-transform",
+            ],
+        ],
+        "mjml" => &[&["dir", "lang", "owa"]],
+        "mj-html-attribute" => &[&["name"]],
+        "mj-raw" => &[&["position"]],
+        "mj-selector" => &[&["path"]],
+        "mj-attributes" | "mj-head" | "mj-html-attributes" | "mj-preview" | "mj-title" => &[],
+        _ => return None,
+    })
+}
+
+/// The syntax expected of an attribute's literal value.
+#[derive(Clone, Copy)]
+enum ValueKind {
+    Color,
+    /// Up to `max` space separated lengths in `px` or `%`.
+    Lengths {
+        max: usize,
+    },
+}
+
+fn value_kind(key: &str) -> Option<ValueKind> {
+    if key.ends_with("color") {
+        return Some(ValueKind::Color);
+    }
+    let max = match key {
+        "padding"
+        | "inner-padding"
+        | "ico-padding"
+        | "icon-padding"
+        | "text-padding"
+        | "border-radius"
+        | "inner-border-radius"
+        | "tb-border-radius" => 4,
+        "width" | "height" | "max-height" | "font-size" | "icon-height" | "icon-size"
+        | "icon-width" | "ico-font-size" | "tb-width" | "background-height"
+        | "background-width" => 1,
+        key if key.contains("padding-") => 1,
+        _ => return None,
+    };
+    Some(ValueKind::Lengths { max })
+}
+
+/// Whether `<tag>` stores its attributes in typed fields, or has none, so that
+/// it has no room for custom or global attributes.
+pub(crate) fn has_typed_attributes(tag: &str) -> bool {
+    matches!(
+        tag,
+        "mj-attributes"
+            | "mj-breakpoint"
+            | "mj-font"
+            | "mj-head"
+            | "mj-html-attribute"
+            | "mj-html-attributes"
+            | "mj-preview"
+            | "mj-raw"
+            | "mj-selector"
+            | "mj-style"
+            | "mj-title"
+            | "mjml"
+    )
+}
+
+/// Emits an error if `attr` is not an attribute of `<tag>`, or if its literal
+/// value is malformed.
+pub(crate) fn validate_attribute(tag: &str, attr: &KeyedAttribute) {
+    let key = attr.key.to_string();
+    if let Err(err) = check_attribute(tag, &key) {
+        return err.emit(attr.key.span());
+    }
+    let Some(Expr::Lit(ExprLit { lit, .. })) = attr.value() else {
+        return;
+    };
+    let value = match lit {
+        Lit::Str(lit) => lit.value(),
+        lit => lit.to_token_stream().to_string(),
+    };
+    if let Err(err) = check_value(&key, &value) {
+        err.emit(lit.span());
+    }
+}
+
+/// Checks that `key` is an attribute of `<tag>`.
+///
+/// Attributes starting with `data-` are never checked, as an escape hatch for
+/// custom attributes, except on elements with [typed
+/// attributes](has_typed_attributes).
+pub(crate) fn check_attribute(tag: &str, key: &str) -> Result<(), MarkupError> {
+    let typed = has_typed_attributes(tag);
+    if key.starts_with("data-") && !typed {
+        return Ok(());
+    }
+    let Some(attributes) = element_attributes(tag) else {
+        return Ok(());
+    };
+    let global = if typed { &[] } else { GLOBAL };
+    let known = || {
+        global
+            .iter()
+            .chain(attributes.iter().copied().flatten())
+            .copied()
+    };
+    if known().any(|known| known == key) {
+        return Ok(());
+    }
+    let err = MarkupError::new(format!("`{key}` is not an attribute of <{tag}>"));
+    let err = match did_you_mean(key, known()) {
+        Some(suggestion) => err.help(format!("did you mean `{suggestion}`?")),
+        None => err,
+    };
+    Err(if typed {
+        err.note(format!("<{tag}> cannot store custom attributes"))
+    } else {
+        err.note("prefix custom attributes with `data-` to skip this check")
+    })
+}
+
+/// Checks the syntax of the literal `value` of the attribute `key`.
+pub(crate) fn check_value(key: &str, value: &str) -> Result<(), MarkupError> {
+    match value_kind(key) {
+        Some(ValueKind::Color) if !is_color(value) => Err(MarkupError::new(format!(
+            "`{value}` is not a valid color for `{key}`"
+        ))
+        .help("use a hex color like `#ff6600`, `rgb(...)` or a color name")),
+        Some(ValueKind::Lengths { max }) if !is_lengths(value, max) => {
+            let expected = if max == 1 {
+                "a length".to_string()
+            } else {
+                format!("1 to {max} lengths")
+            };
+            Err(
+                MarkupError::new(format!("`{value}` is not a valid value for `{key}`")).help(
+                    format!("`{key}` expects {expected} in `px` or `%`, like `10px` or `50%`"),
+                ),
+            )
+        }
+        _ => Ok(()),
+    }
+}
+
+fn is_color(value: &str) -> bool {
+    if let Some(hex) = value.strip_prefix('#') {
+        return matches!(hex.len(), 3 | 4 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit());
+    }
+    if let Some(args) = ["rgb(", "rgba(", "hsl(", "hsla("]
+        .iter()
+        .find_map(|prefix| value.strip_prefix(prefix))
+    {
+        return args.ends_with(')');
+    }
+    !value.is_empty() && value.chars().all(|c| c.is_ascii_alphabetic())
+}
+
+fn is_lengths(value: &str, max: usize) -> bool {
+    let parts = value.split_whitespace().collect::<Vec<_>>();
+    (1..=max).contains(&parts.len()) && parts.into_iter().all(is_length)
+}
+
+fn is_length(value: &str) -> bool {
+    if value == "auto" {
+        return true;
+    }
+    let number = value
+        .strip_suffix("px")
+        .or_else(|| value.strip_suffix('%'))
+        .unwrap_or(value);
+    // Only zero may omit its unit.
+    (number != value || number.trim_start_matches(['0', '.']).is_empty())
+        && !number.is_empty()
+        && number.parse::<f64>().is_ok_and(|n| n >= 0.0)
+}
@@ -0,0 +1,340 @@
+use convert_case::Case::Pascal;
+use convert_case::Casing;
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::spanned::Spanned;
+use syn::visit_mut::VisitMut;
+use syn::{
+    parse_quote, Attribute, Expr, FnArg, GenericParam, Ident, ItemFn, Lifetime, Pat, Type,
+    TypeReference,
+};
+
+use crate::view::mrmx_path;
+
+struct Prop {
+    attrs: Vec<Attribute>,
+    mutability: Option<syn::token::Mut>,
+    name: Ident,
+    ty: Type,
+    default: Option<Expr>,
+    into: bool,
+}
+
+impl Prop {
+    fn from_arg(arg: &FnArg) -> Prop {
+        let arg = match arg {
+            FnArg::Typed(arg) => arg,
+            FnArg::Receiver(receiver) => {
+                proc_macro_error::abort!(receiver.span(), "Components cannot take `self`")
+            }
+        };
+        let Pat::Ident(pat) = &*arg.pat else {
+            proc_macro_error::abort!(arg.pat.span(), "Component props must be plain identifiers")
+        };
+        if let Type::ImplTrait(ty) = &*arg.ty {
+            proc_macro_error::abort!(
+                ty.span(),
+                "Component props cannot use `impl Trait`";
+                help = "add a generic parameter to the component instead"
+            )
+        }
+
+        let mut prop = Prop {
+            attrs: Vec::new(),
+            mutability: pat.mutability,
+            name: pat.ident.clone(),
+            ty: (*arg.ty).clone(),
+            default: None,
+            into: false,
+        };
+        for attr in &arg.attrs {
+            if !attr.path().is_ident("prop") {
+                prop.attrs.push(attr.clone());
+                continue;
+            }
+            let parsed = attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("default") {
+                    prop.default = Some(if meta.input.peek(syn::Token![=]) {
+                        meta.value()?.parse()?
+                    } else {
+                        parse_quote! { ::core::default::Default::default() }
+                    });
+                    Ok(())
+                } else if meta.path.is_ident("into") {
+                    prop.into = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `default`, `default = ...` or `into`"))
+                }
+            });
+            if let Err(err) = parsed {
+                proc_macro_error::abort!(err.span(), err.to_string())
+            }
+        }
+        prop
+    }
+
+    fn is_required(&self) -> bool {
+        self.default.is_none()
+    }
+}
+
+/// Gives every elided lifetime in a prop type a name, since the props struct
+/// cannot elide them.
+struct NameElidedLifetimes<'a> {
+    lifetime: &'a Lifetime,
+    used: bool,
+}
+
+impl VisitMut for NameElidedLifetimes<'_> {
+    fn visit_type_reference_mut(&mut self, ty: &mut TypeReference) {
+        if ty.lifetime.is_none() {
+            ty.lifetime = Some(self.lifetime.clone());
+            self.used = true;
+        }
+        syn::visit_mut::visit_type_reference_mut(self, ty);
+    }
+
+    fn visit_lifetime_mut(&mut self, lifetime: &mut Lifetime) {
+        if lifetime.ident == "_" {
+            *lifetime = self.lifetime.clone();
+            self.used = true;
+        }
+    }
+}
+
+pub fn component_to_tokens(item: ItemFn) -> TokenStream {
+    let ItemFn {
+        attrs,
+        vis,
+        sig,
+        block,
+    } = item;
+    let name = &sig.ident;
+    let output = &sig.output;
+    let props_name = format_ident!("{name}Props");
+    let builder_name = format_ident!("{name}PropsBuilder");
+
+    let mut props = sig.inputs.iter().map(Prop::from_arg).collect::<Vec<_>>();
+
+    let mut generics = sig.generics.clone();
+    let lifetime = Lifetime::new("'props", Span::call_site());
+    let mut elided = NameElidedLifetimes {
+        lifetime: &lifetime,
+        used: false,
+    };
+    for prop in &mut props {
+        elided.visit_type_mut(&mut prop.ty);
+    }
+    if elided.used {
+        generics.params.insert(0, parse_quote! { #lifetime });
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let field_attrs = props.iter().map(|p| &p.attrs).collect::<Vec<_>>();
+    let field_names = props.iter().map(|p| &p.name).collect::<Vec<_>>();
+    let field_types = props.iter().map(|p| &p.ty).collect::<Vec<_>>();
+    let field_patterns = props.iter().map(|p| {
+        let mutability = &p.mutability;
+        let name = &p.name;
+        quote! { #mutability #name }
+    });
+
+    // Each required prop is tracked by a type parameter on the builder, which
+    // is `()` until the prop is set and `(T,)` afterwards. `build` is only
+    // callable once every state implements the prop's marker trait.
+    let required = props
+        .iter()
+        .map(|p| {
+            p.is_required().then(|| {
+                let prop = p.name.to_string().to_case(Pascal);
+                (
+                    format_ident!("__{prop}", span = p.name.span()),
+                    format_ident!("__{props_name}{prop}", span = p.name.span()),
+                )
+            })
+        })
+        .collect::<Vec<_>>();
+    let required_states = required
+        .iter()
+        .flatten()
+        .map(|(state, _)| state)
+        .collect::<Vec<_>>();
+
+    let mut builder_generics = generics.clone();
+    for state in &required_states {
+        builder_generics.params.push(parse_quote! { #state });
+    }
+    let (builder_impl_generics, builder_ty_generics, _) = builder_generics.split_for_impl();
+    let generic_args = generics
+        .params
+        .iter()
+        .map(|param| match param {
+            GenericParam::Lifetime(param) => {
+                let lifetime = &param.lifetime;
+                quote! { #lifetime }
+            }
+            GenericParam::Type(param) => {
+                let ident = &param.ident;
+                quote! { #ident }
+            }
+            GenericParam::Const(param) => {
+                let ident = &param.ident;
+                quote! { #ident }
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let builder_fields = props.iter().zip(&required).map(|(p, required)| {
+        let name = &p.name;
+        let ty = &p.ty;
+        match required {
+            Some((state, _)) => quote! { #name: #state },
+            None => quote! { #name: ::core::option::Option<#ty> },
+        }
+    });
+    let empty_fields = props.iter().map(|p| {
+        let name = &p.name;
+        if p.is_required() {
+            quote! { #name: () }
+        } else {
+            quote! { #name: ::core::option::Option::None }
+        }
+    });
+    let unset_states = required_states.iter().map(|_| quote! { () });
+
+    let setters = props.iter().zip(&required).map(|(p, required)| {
+        let name = &p.name;
+        let ty = &p.ty;
+        let (param_ty, value) = if p.into {
+            (
+                quote! { impl ::core::convert::Into<#ty> },
+                quote! { ::core::convert::Into::into(#name) },
+            )
+        } else {
+            (quote! { #ty }, quote! { #name })
+        };
+        let others = props
+            .iter()
+            .filter(|other| other.name != p.name)
+            .map(|other| &other.name);
+        match required {
+            Some((state, _)) => {
+                let next_states = required_states.iter().map(|s| {
+                    if *s == state {
+                        quote! { (#ty,) }
+                    } else {
+                        quote! { #s }
+                    }
+                });
+                quote! {
+                    pub fn #name(self, #name: #param_ty) -> #builder_name<#(#generic_args,)* #(#next_states),*> {
+                        #builder_name {
+                            #name: (#value,),
+                            #(#others: self.#others,)*
+                            __props: ::core::marker::PhantomData,
+                        }
+                    }
+                }
+            }
+            None => quote! {
+                pub fn #name(mut self, #name: #param_ty) -> Self {
+                    self.#name = ::core::option::Option::Some(#value);
+                    self
+                }
+            },
+        }
+    });
+
+    let build_values = props.iter().zip(&required).map(|(p, required)| {
+        let name = &p.name;
+        match (required, &p.default) {
+            (Some((_, marker)), _) => quote! { #name: #marker::value(self.#name) },
+            (None, Some(default)) => quote! {
+                #name: self.#name.unwrap_or_else(|| #default)
+            },
+            (None, None) => unreachable!(),
+        }
+    });
+    let build_bounds = props
+        .iter()
+        .zip(&required)
+        .filter_map(|(p, required)| {
+            let (state, marker) = required.as_ref()?;
+            let ty = &p.ty;
+            Some(quote! { #state: #marker<#ty> })
+        })
+        .collect::<Vec<_>>();
+    let build_where = (!build_bounds.is_empty()).then(|| quote! { where #(#build_bounds,)* });
+
+    let markers = props.iter().zip(&required).filter_map(|(p, required)| {
+        let (_, marker) = required.as_ref()?;
+        let message = format!("missing required prop `{}` on `<{name}>`", p.name);
+        let label = format!("add `{}` to `<{name}>`", p.name);
+        Some(quote! {
+            #[doc(hidden)]
+            #[diagnostic::on_unimplemented(message = #message, label = #label)]
+            #vis trait #marker<T> {
+                fn value(self) -> T;
+            }
+
+            impl<T> #marker<T> for (T,) {
+                fn value(self) -> T {
+                    self.0
+                }
+            }
+        })
+    });
+
+    let props_doc = format!("Props for the [`{name}`] component.");
+    let builder_doc = format!("Builder for [`{props_name}`].");
+    let constness = &sig.constness;
+    let asyncness = &sig.asyncness;
+    let unsafety = &sig.unsafety;
+    let mrmx = mrmx_path();
+
+    quote! {
+        #[doc = #props_doc]
+        #vis struct #props_name #impl_generics #where_clause {
+            #(#(#field_attrs)* pub #field_names: #field_types,)*
+        }
+
+        #[doc = #builder_doc]
+        #vis struct #builder_name #builder_impl_generics #where_clause {
+            #(#builder_fields,)*
+            __props: ::core::marker::PhantomData<fn() -> #props_name #ty_generics>,
+        }
+
+        impl #impl_generics #mrmx::Props for #props_name #ty_generics #where_clause {
+            type Builder = #builder_name<#(#generic_args,)* #(#unset_states),*>;
+
+            fn builder() -> Self::Builder {
+                #builder_name {
+                    #(#empty_fields,)*
+                    __props: ::core::marker::PhantomData,
+                }
+            }
+        }
+
+        #(#markers)*
+
+        impl #builder_impl_generics #builder_name #builder_ty_generics #where_clause {
+            #(#setters)*
+
+            pub fn build(self) -> #props_name #ty_generics
+            #build_where
+            {
+                #props_name {
+                    #(#build_values,)*
+                }
+            }
+        }
+
+        #(#attrs)*
+        #[allow(non_snake_case)]
+        #vis #constness #asyncness #unsafety fn #name #impl_generics(props: #props_name #ty_generics) #output #where_clause {
+            let #props_name { #(#field_patterns,)* } = props;
+            #block
+        }
+    }
+}
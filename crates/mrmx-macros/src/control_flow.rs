@@ -0,0 +1,393 @@
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use rstml::node::{CustomNode, Node};
+use rstml::recoverable::{ParseRecoverable, RecoverableContext};
+use syn::parse::{ParseBuffer, ParseStream};
+use syn::token::Brace;
+use syn::{braced, Block, Expr, Pat, Token};
+
+use crate::view::{children_to_tokens, push_children, CustomChildren, TagType};
+
+/// Markup that is repeated or chosen at runtime, written as a braced Rust
+/// control flow expression whose bodies are markup:
+///
+/// ```text
+/// {for item in items { <mj-text>{item}</mj-text> }}
+/// {if cond { <mj-text>yes</mj-text> } else { <mj-text>no</mj-text> }}
+/// {match method { Shipping::Express => <mj-text>fast</mj-text>, _ => {} }}
+/// ```
+///
+/// An `if` or `match` block that already parses as Rust is left as a plain
+/// block, so that it can still evaluate to a single child.
+#[derive(Clone, Debug)]
+pub struct ControlFlow {
+    pub brace: Brace,
+    pub kind: ControlFlowKind,
+}
+
+#[derive(Clone, Debug)]
+pub enum ControlFlowKind {
+    For(ForNode),
+    If(IfNode),
+    Match(MatchNode),
+}
+
+#[derive(Clone, Debug)]
+pub struct ForNode {
+    pub for_token: Token![for],
+    pub pat: Pat,
+    pub in_token: Token![in],
+    pub expr: Expr,
+    pub body: Body,
+}
+
+#[derive(Clone, Debug)]
+pub struct IfNode {
+    pub if_token: Token![if],
+    pub cond: Expr,
+    pub then_branch: Body,
+    pub else_branch: Option<(Token![else], ElseBranch)>,
+}
+
+#[derive(Clone, Debug)]
+pub enum ElseBranch {
+    If(Box<IfNode>),
+    Else(Body),
+}
+
+#[derive(Clone, Debug)]
+pub struct MatchNode {
+    pub match_token: Token![match],
+    pub expr: Expr,
+    pub arms_brace: Brace,
+    pub arms: Vec<MatchArm>,
+}
+
+#[derive(Clone, Debug)]
+pub struct MatchArm {
+    pub pat: Pat,
+    pub guard: Option<(Token![if], Expr)>,
+    pub fat_arrow: Token![=>],
+    pub body: Body,
+    pub comma: Option<Token![,]>,
+}
+
+/// The markup of a braced branch, or the single node of an unbraced `match`
+/// arm.
+#[derive(Clone, Debug)]
+pub struct Body {
+    pub brace: Option<Brace>,
+    pub nodes: Vec<Node<ControlFlow>>,
+}
+
+impl CustomNode for ControlFlow {
+    fn peek_element(input: ParseStream) -> bool {
+        let Ok((_, content)) = braced_content(input) else {
+            return false;
+        };
+        if content.peek(Token![for]) {
+            true
+        } else if content.peek(Token![if]) || content.peek(Token![match]) {
+            !is_rust_block(&content)
+        } else {
+            false
+        }
+    }
+}
+
+fn is_rust_block(input: ParseStream) -> bool {
+    let fork = input.fork();
+    fork.call(Block::parse_within).is_ok() && fork.is_empty()
+}
+
+impl ParseRecoverable for ControlFlow {
+    fn parse_recoverable(parser: &mut RecoverableContext, input: ParseStream) -> Option<Self> {
+        let (brace, content) = parser.save_diagnostics(braced_content(input))?;
+        let kind = if content.peek(Token![for]) {
+            ControlFlowKind::For(ForNode {
+                for_token: parser.parse_simple(&content)?,
+                pat: parser.save_diagnostics(Pat::parse_multi_with_leading_vert(&content))?,
+                in_token: parser.parse_simple(&content)?,
+                expr: parser.save_diagnostics(Expr::parse_without_eager_brace(&content))?,
+                body: parse_braced_body(parser, &content)?,
+            })
+        } else if content.peek(Token![if]) {
+            ControlFlowKind::If(parse_if(parser, &content)?)
+        } else {
+            ControlFlowKind::Match(parse_match(parser, &content)?)
+        };
+        if !content.is_empty() {
+            parser.push_diagnostic(content.error("unexpected tokens after control flow"));
+        }
+        Some(ControlFlow { brace, kind })
+    }
+}
+
+fn parse_if(parser: &mut RecoverableContext, input: ParseStream) -> Option<IfNode> {
+    let if_token = parser.parse_simple(input)?;
+    let cond = parser.save_diagnostics(Expr::parse_without_eager_brace(input))?;
+    let then_branch = parse_braced_body(parser, input)?;
+    let else_branch = if input.peek(Token![else]) {
+        let else_token = parser.parse_simple(input)?;
+        let branch = if input.peek(Token![if]) {
+            ElseBranch::If(Box::new(parse_if(parser, input)?))
+        } else {
+            ElseBranch::Else(parse_braced_body(parser, input)?)
+        };
+        Some((else_token, branch))
+    } else {
+        None
+    };
+    Some(IfNode {
+        if_token,
+        cond,
+        then_branch,
+        else_branch,
+    })
+}
+
+fn parse_match(parser: &mut RecoverableContext, input: ParseStream) -> Option<MatchNode> {
+    let match_token = parser.parse_simple(input)?;
+    let expr = parser.save_diagnostics(Expr::parse_without_eager_brace(input))?;
+    let (arms_brace, content) = parser.save_diagnostics(braced_content(input))?;
+    let mut arms = Vec::new();
+    while !content.is_empty() {
+        let pat = parser.save_diagnostics(Pat::parse_multi_with_leading_vert(&content))?;
+        let guard = if content.peek(Token![if]) {
+            Some((
+                parser.parse_simple(&content)?,
+                parser.parse_simple(&content)?,
+            ))
+        } else {
+            None
+        };
+        let fat_arrow = parser.parse_simple(&content)?;
+        let body = if content.peek(Brace) {
+            parse_braced_body(parser, &content)?
+        } else {
+            Body {
+                brace: None,
+                nodes: vec![parser.parse_recoverable(&content)?],
+            }
+        };
+        let comma = if content.peek(Token![,]) {
+            Some(parser.parse_simple(&content)?)
+        } else {
+            None
+        };
+        arms.push(MatchArm {
+            pat,
+            guard,
+            fat_arrow,
+            body,
+            comma,
+        });
+    }
+    Some(MatchNode {
+        match_token,
+        expr,
+        arms_brace,
+        arms,
+    })
+}
+
+fn braced_content(input: ParseStream) -> syn::Result<(Brace, ParseBuffer)> {
+    let content;
+    let brace = braced!(content in input);
+    Ok((brace, content))
+}
+
+fn parse_braced_body(parser: &mut RecoverableContext, input: ParseStream) -> Option<Body> {
+    let (brace, content) = parser.save_diagnostics(braced_content(input))?;
+    let mut nodes = Vec::new();
+    while !content.is_empty() {
+        match parser.parse_recoverable(&content) {
+            Some(node) => nodes.push(node),
+            None => break,
+        }
+    }
+    Some(Body {
+        brace: Some(brace),
+        nodes,
+    })
+}
+
+impl ToTokens for ControlFlow {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.brace.surround(tokens, |tokens| match &self.kind {
+            ControlFlowKind::For(node) => node.to_tokens(tokens),
+            ControlFlowKind::If(node) => node.to_tokens(tokens),
+            ControlFlowKind::Match(node) => node.to_tokens(tokens),
+        });
+    }
+}
+
+impl ToTokens for ForNode {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let ForNode {
+            for_token,
+            pat,
+            in_token,
+            expr,
+            body,
+        } = self;
+        tokens.extend(quote! { #for_token #pat #in_token #expr #body });
+    }
+}
+
+impl ToTokens for IfNode {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let IfNode {
+            if_token,
+            cond,
+            then_branch,
+            else_branch,
+        } = self;
+        tokens.extend(quote! { #if_token #cond #then_branch });
+        match else_branch {
+            Some((else_token, ElseBranch::If(node))) => {
+                tokens.extend(quote! { #else_token #node });
+            }
+            Some((else_token, ElseBranch::Else(body))) => {
+                tokens.extend(quote! { #else_token #body });
+            }
+            None => {}
+        }
+    }
+}
+
+impl ToTokens for MatchNode {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let MatchNode {
+            match_token, expr, ..
+        } = self;
+        tokens.extend(quote! { #match_token #expr });
+        self.arms_brace.surround(tokens, |tokens| {
+            for arm in &self.arms {
+                let MatchArm {
+                    pat,
+                    guard,
+                    fat_arrow,
+                    body,
+                    comma,
+                } = arm;
+                let guard = guard_to_tokens(guard);
+                tokens.extend(quote! { #pat #guard #fat_arrow #body #comma });
+            }
+        });
+    }
+}
+
+impl ToTokens for Body {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let nodes = &self.nodes;
+        match &self.brace {
+            Some(brace) => brace.surround(tokens, |tokens| tokens.extend(quote! { #(#nodes)* })),
+            None => tokens.extend(quote! { #(#nodes)* }),
+        }
+    }
+}
+
+fn guard_to_tokens(guard: &Option<(Token![if], Expr)>) -> Option<TokenStream> {
+    guard
+        .as_ref()
+        .map(|(if_token, cond)| quote! { #if_token #cond })
+}
+
+impl CustomChildren for ControlFlow {
+    fn children_to_tokens(&self, parent_type: TagType) -> TokenStream {
+        match &self.kind {
+            ControlFlowKind::For(node) => {
+                let ForNode {
+                    for_token,
+                    pat,
+                    in_token,
+                    expr,
+                    body,
+                } = node;
+                let body = body_to_tokens(body, parent_type);
+                quote! {
+                    #for_token #pat #in_token #expr #body
+                }
+            }
+            ControlFlowKind::If(node) => if_to_tokens(node, parent_type),
+            ControlFlowKind::Match(node) => {
+                let MatchNode {
+                    match_token,
+                    expr,
+                    arms,
+                    ..
+                } = node;
+                let arms = arms.iter().map(|arm| {
+                    let pat = &arm.pat;
+                    let guard = guard_to_tokens(&arm.guard);
+                    let fat_arrow = &arm.fat_arrow;
+                    let body = body_to_tokens(&arm.body, parent_type);
+                    quote! { #pat #guard #fat_arrow #body }
+                });
+                quote! {
+                    #match_token #expr {
+                        #(#arms)*
+                    }
+                }
+            }
+        }
+    }
+
+    fn bodies(&self) -> Vec<&[Node<Self>]> {
+        match &self.kind {
+            ControlFlowKind::For(node) => vec![&node.body.nodes],
+            ControlFlowKind::If(node) => {
+                let mut bodies = Vec::new();
+                let mut node = node;
+                loop {
+                    bodies.push(node.then_branch.nodes.as_slice());
+                    match &node.else_branch {
+                        Some((_, ElseBranch::If(next))) => node = next,
+                        Some((_, ElseBranch::Else(body))) => {
+                            bodies.push(&body.nodes);
+                            break;
+                        }
+                        None => break,
+                    }
+                }
+                bodies
+            }
+            ControlFlowKind::Match(node) => node
+                .arms
+                .iter()
+                .map(|arm| arm.body.nodes.as_slice())
+                .collect(),
+        }
+    }
+}
+
+fn if_to_tokens(node: &IfNode, parent_type: TagType) -> TokenStream {
+    let IfNode {
+        if_token,
+        cond,
+        then_branch,
+        else_branch,
+    } = node;
+    let then_branch = body_to_tokens(then_branch, parent_type);
+    let else_branch = else_branch.as_ref().map(|(else_token, branch)| {
+        let branch = match branch {
+            ElseBranch::If(node) => if_to_tokens(node, parent_type),
+            ElseBranch::Else(body) => body_to_tokens(body, parent_type),
+        };
+        quote! { #else_token #branch }
+    });
+    quote! {
+        #if_token #cond #then_branch #else_branch
+    }
+}
+
+/// Returns a block pushing every node of `body` onto the parent's children.
+fn body_to_tokens(body: &Body, parent_type: TagType) -> TokenStream {
+    let children = push_children(children_to_tokens(&body.nodes, parent_type));
+    quote! {
+        {
+            #children
+        }
+    }
+}
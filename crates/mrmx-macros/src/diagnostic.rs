@@ -0,0 +1,57 @@
+use proc_macro2::Span;
+use proc_macro_error::{Diagnostic, Level};
+
+/// An error in the markup found by the checks shared by `view!` and
+/// `mjml_file!`, which report it on a span of the macro input or at a line
+/// and column of the template.
+pub(crate) struct MarkupError {
+    message: String,
+    help: Option<String>,
+    note: Option<String>,
+}
+
+impl MarkupError {
+    pub(crate) fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            help: None,
+            note: None,
+        }
+    }
+
+    pub(crate) fn help(mut self, help: impl Into<String>) -> Self {
+        self.help = Some(help.into());
+        self
+    }
+
+    pub(crate) fn note(mut self, note: impl Into<String>) -> Self {
+        self.note = Some(note.into());
+        self
+    }
+
+    /// Emits the error on `span`.
+    pub(crate) fn emit(self, span: Span) {
+        self.emit_message(span, None);
+    }
+
+    /// Emits the error on `span`, prefixed with `location`, like
+    /// `templates/welcome.mjml:12:7`.
+    pub(crate) fn emit_at(self, span: Span, location: &str) {
+        self.emit_message(span, Some(location));
+    }
+
+    fn emit_message(self, span: Span, location: Option<&str>) {
+        let message = match location {
+            Some(location) => format!("{location}: {}", self.message),
+            None => self.message,
+        };
+        let mut diagnostic = Diagnostic::spanned(span, Level::Error, message);
+        if let Some(help) = self.help {
+            diagnostic = diagnostic.help(help);
+        }
+        if let Some(note) = self.note {
+            diagnostic = diagnostic.note(note);
+        }
+        diagnostic.emit();
+    }
+}
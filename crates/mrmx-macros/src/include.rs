@@ -0,0 +1,302 @@
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use rstml::node::{NodeAttribute, NodeElement};
+use syn::spanned::Spanned;
+use syn::{Expr, ExprLit, Lit, LitStr};
+
+use crate::mjml_file::Template;
+use crate::view::{
+    children_ident, element_children_to_tokens, mrmx_path, Child, CustomChildren, TagType,
+};
+
+thread_local! {
+    /// The templates and partials being expanded, innermost last.
+    static INCLUDING: RefCell<Vec<Including>> = const { RefCell::new(Vec::new()) };
+}
+
+struct Including {
+    file: PathBuf,
+    /// Whether the file is a partial, whose own includes are inlined, since
+    /// mrml has no room for an include in an include.
+    partial: bool,
+}
+
+/// What `<mj-include>` embeds, from its `type` attribute.
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    Mjml,
+    Html,
+    Css { inline: bool },
+}
+
+/// Expands `<mj-include path="..." />` into the tree of the file at compile
+/// time, with the same checks as `view!`. The path is resolved relative to the
+/// including file, or to the calling crate's manifest in `view!`, and
+/// `include_str!` makes the compiler rebuild the view when the file changes.
+pub(crate) fn include_to_tokens<C>(node: &NodeElement<C>, parent_type: TagType) -> Child
+where
+    C: CustomChildren,
+{
+    let head = parent_type == TagType::MjmlHead;
+    let (path, kind) = include_attributes(node);
+    let file = resolve(&path.value());
+    if INCLUDING.with_borrow(|including| including.iter().any(|including| including.file == file)) {
+        proc_macro_error::abort!(path.span(), "`{}` includes itself", path.value())
+    }
+    let content = read(&file, path.span());
+
+    let mrmx = mrmx_path();
+    let text = LitStr::new(&content, path.span());
+    let children = match kind {
+        Kind::Mjml => within(&file, true, || {
+            partial_to_tokens(&file, &content, path.span(), head)
+        }),
+        Kind::Html if !head => Some(quote! {
+            vec![::core::convert::Into::into(#mrmx::WithChildren::with_children(
+                #mrmx::mrml::mj_raw::MjRaw::default(),
+                vec![::core::convert::Into::into(#mrmx::mrml::text::Text::from(#text))],
+            ))]
+        }),
+        Kind::Css { .. } if !head => {
+            proc_macro_error::abort!(path.span(), "CSS can only be included in <mj-head>")
+        }
+        Kind::Html | Kind::Css { .. } => Some(quote! {
+            vec![::core::convert::Into::into(#mrmx::mrml::text::Text::from(#text))]
+        }),
+    };
+
+    let file = file.to_string_lossy();
+    let rebuild = quote! { const _: &str = ::core::include_str!(#file); };
+    if in_partial() {
+        let vec = children_ident();
+        let child = syn::Ident::new("child", Span::mixed_site());
+        let children = children.map(|children| {
+            quote! {
+                for #child in #children {
+                    #vec.push(#child);
+                }
+            }
+        });
+        return Child::Many(quote! {
+            #rebuild
+            #children
+        });
+    }
+
+    let include = if head {
+        quote! { #mrmx::mrml::mj_include::head::MjIncludeHead }
+    } else {
+        quote! { #mrmx::mrml::mj_include::body::MjIncludeBody }
+    };
+    let path = path.value();
+    let attributes = match kind {
+        Kind::Mjml => vec![("path", path.as_str())],
+        Kind::Html => vec![("path", path.as_str()), ("type", "html")],
+        Kind::Css { inline: false } => vec![("path", path.as_str()), ("type", "css")],
+        Kind::Css { inline: true } => vec![
+            ("path", path.as_str()),
+            ("type", "css"),
+            ("css-inline", "inline"),
+        ],
+    };
+    let element =
+        attributes
+            .into_iter()
+            .fold(quote! { #include::default() }, |element, (key, value)| {
+                quote! {
+                    #mrmx::WithAttribute::with_attribute(
+                        #element,
+                        ::std::string::ToString::to_string(#key),
+                        ::std::string::ToString::to_string(#value),
+                    )
+                }
+            });
+    let element = match children {
+        Some(children) => quote! { #mrmx::WithChildren::with_children(#element, #children) },
+        None => element,
+    };
+    let element = quote! {
+        {
+            #rebuild
+            #element
+        }
+    };
+    if parent_type == TagType::Root {
+        Child::Single(element)
+    } else {
+        Child::Single(quote! { ::core::convert::Into::into(#element) })
+    }
+}
+
+/// Reads the `path`, `type` and `css-inline` attributes of `<mj-include>`.
+fn include_attributes<C>(node: &NodeElement<C>) -> (&LitStr, Kind)
+where
+    C: CustomChildren,
+{
+    let mut path = None;
+    let mut kind = Kind::Mjml;
+    let mut inline = None;
+    for attr in node.attributes() {
+        let NodeAttribute::Attribute(attr) = attr else {
+            proc_macro_error::abort!(
+                attr.span(),
+                "<mj-include> does not support spread attributes"
+            )
+        };
+        let key = attr.key.to_string();
+        let lit = match attr.value() {
+            Some(Expr::Lit(ExprLit {
+                lit: Lit::Str(lit), ..
+            })) => lit,
+            value => proc_macro_error::abort!(
+                value.map_or(attr.key.span(), Spanned::span),
+                "<mj-include> needs a string literal `{}`", key;
+                help = "call `mrmx::include::include_body` from a block to choose \
+                        the file at runtime"
+            ),
+        };
+        match (key.as_str(), lit.value().as_str()) {
+            ("path", _) => path = Some(lit),
+            ("type", "mjml") => kind = Kind::Mjml,
+            ("type", "html") => kind = Kind::Html,
+            ("type", "css") => kind = Kind::Css { inline: false },
+            ("css-inline", "inline") => inline = Some(attr.key.span()),
+            ("type" | "css-inline", value) => proc_macro_error::abort!(
+                lit.span(),
+                "`{}` is not a valid value for `{}`", value, key;
+                help = "`type` is `mjml`, `html` or `css`, and `css-inline` is `inline`"
+            ),
+            _ => proc_macro_error::emit_error!(
+                attr.key.span(),
+                "<mj-include> only supports the `path`, `type` and `css-inline` attributes"
+            ),
+        }
+    }
+    if let Some(span) = inline {
+        match &mut kind {
+            Kind::Css { inline } => *inline = true,
+            _ => proc_macro_error::emit_error!(span, "`css-inline` needs `type=\"css\"`"),
+        }
+    }
+    let Some(path) = path else {
+        proc_macro_error::abort!(node.name().span(), "<mj-include> needs a `path` attribute")
+    };
+    (path, kind)
+}
+
+/// Checks the MJML partial `content` of `file`, with the checks of `view!` and
+/// with mrml's parser, and builds its children the way `view!` would.
+fn partial_to_tokens(file: &Path, content: &str, span: Span, head: bool) -> Option<TokenStream> {
+    let (section, parent_type) = if head {
+        ("mj-head", TagType::MjmlHead)
+    } else {
+        ("mj-body", TagType::Mjml)
+    };
+    let path = display_path(file);
+    let template = Template {
+        source: content,
+        path: &path,
+        span,
+        placeholders: false,
+    };
+    let nodes = template.parse();
+    template.check_with_mrml(&nodes, Some(section));
+    let nodes = document_children(nodes, section);
+    template.check(&nodes, section);
+    proc_macro_error::abort_if_dirty();
+
+    let tokens = template.nodes(&nodes, section);
+    let parser = rstml::Parser::new(rstml::ParserConfig::default());
+    let nodes = match parser.parse_simple(tokens) {
+        Ok(nodes) => nodes,
+        Err(err) => proc_macro_error::abort!(span, "cannot expand `{}`: {}", path, err),
+    };
+    element_children_to_tokens(&nodes, parent_type)
+}
+
+/// The children of `<section>` when `nodes` is a whole `<mjml>` document,
+/// which a partial can be, or else `nodes`.
+fn document_children<'a>(
+    mut nodes: Vec<mrmx_markup::parse::Node<'a>>,
+    section: &str,
+) -> Vec<mrmx_markup::parse::Node<'a>> {
+    use mrmx_markup::parse::Node;
+
+    let Some(index) = nodes
+        .iter()
+        .position(|node| matches!(node, Node::Element(element) if element.tag == "mjml"))
+    else {
+        return nodes;
+    };
+    let Node::Element(mjml) = nodes.swap_remove(index) else {
+        unreachable!("the node was matched as an element")
+    };
+    mjml.children
+        .into_iter()
+        .find_map(|node| match node {
+            Node::Element(element) if element.tag == section => Some(element.children),
+            _ => None,
+        })
+        .unwrap_or_default()
+}
+
+/// Runs `f` with relative includes resolved against `file`, which is a
+/// `partial` or a template.
+pub(crate) fn within<T>(file: &Path, partial: bool, f: impl FnOnce() -> T) -> T {
+    struct Pop;
+
+    impl Drop for Pop {
+        fn drop(&mut self) {
+            INCLUDING.with_borrow_mut(|including| including.pop());
+        }
+    }
+
+    INCLUDING.with_borrow_mut(|including| {
+        including.push(Including {
+            file: file.to_path_buf(),
+            partial,
+        })
+    });
+    let _pop = Pop;
+    f()
+}
+
+/// Whether a partial is being expanded.
+fn in_partial() -> bool {
+    INCLUDING.with_borrow(|including| including.last().is_some_and(|including| including.partial))
+}
+
+fn manifest_dir() -> PathBuf {
+    PathBuf::from(std::env::var_os("CARGO_MANIFEST_DIR").unwrap_or_default())
+}
+
+/// Resolves `path` relative to the file including it, or to the calling
+/// crate's manifest.
+pub(crate) fn resolve(path: &str) -> PathBuf {
+    let including = INCLUDING.with_borrow(|including| {
+        including
+            .last()
+            .and_then(|including| including.file.parent().map(Path::to_path_buf))
+    });
+    including.unwrap_or_else(manifest_dir).join(path)
+}
+
+/// The path of `file` relative to the calling crate's manifest, for errors.
+pub(crate) fn display_path(file: &Path) -> String {
+    let manifest_dir = manifest_dir();
+    file.strip_prefix(&manifest_dir)
+        .unwrap_or(file)
+        .display()
+        .to_string()
+}
+
+/// Reads `file`, reporting an error on `span` if it cannot be read.
+pub(crate) fn read(file: &Path, span: Span) -> String {
+    match std::fs::read_to_string(file) {
+        Ok(content) => content,
+        Err(err) => proc_macro_error::abort!(span, "cannot read `{}`: {}", file.display(), err),
+    }
+}
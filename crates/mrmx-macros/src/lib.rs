@@ -6,25 +6,97 @@
 //! ```
 //!
 //! Please see the `mrmx` crate for more information.
+mod attributes;
+mod component;
+mod control_flow;
+mod diagnostic;
+mod include;
+mod mjml_file;
+mod nesting;
+mod options;
+mod suggest;
+mod urls;
 mod view;
 use quote::quote;
 
-#[proc_macro_error::proc_macro_error]
+// `proc_macro_hack` wraps emitted errors in a block, so that reporting more
+// than one of them is still a valid expression.
+#[proc_macro_error::proc_macro_error(proc_macro_hack)]
 #[proc_macro]
 #[cfg_attr(debug_assertions, tracing::instrument(level = "trace", skip_all))]
 pub fn view(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    let tokens: proc_macro2::TokenStream = tokens.into();
+    let input = syn::parse_macro_input!(tokens as options::WithOptions<proc_macro2::TokenStream>);
+    input.expand(|tokens| {
+        let config = rstml::ParserConfig::default()
+            .recover_block(true)
+            .custom_node::<control_flow::ControlFlow>();
+        let parser = rstml::Parser::new(config);
+        let (nodes, errors) = parser.parse_recoverable(tokens).split_vec();
+        let errors = errors.into_iter().map(|e| e.emit_as_expr_tokens());
+        let nodes_output = view::render_view(&nodes);
+        quote! {
+            {
+                #(#errors;)*
+                #nodes_output
+            }
+        }
+        .into()
+    })
+}
+
+/// Embeds an `.mjml` template, building the same tree as `view!` would from
+/// its markup.
+///
+/// The path is resolved relative to the calling crate's `Cargo.toml`. Text and
+/// attribute values can contain `{expr}` placeholders, which are filled from
+/// the variables in scope; `{{` and `}}` are literal braces. Errors in the
+/// template are reported at their line and column, like
+/// `templates/welcome.mjml:4:20`.
+///
+/// Like in `view!`, options like custom tags or allowed URLs can be given
+/// before the path:
+/// `mjml_file!(#![custom_tags("mj-chart")] "templates/report.mjml")`.
+#[proc_macro_error::proc_macro_error(proc_macro_hack)]
+#[proc_macro]
+pub fn mjml_file(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = syn::parse_macro_input!(tokens as options::WithOptions<syn::LitStr>);
+    input.expand(|path| {
+        let file = include::resolve(&path.value());
+        let content = include::read(&file, path.span());
+        let tokens = mjml_file::mjml_to_view(&content, &path.value(), path.span());
 
-    let config = rstml::ParserConfig::default().recover_block(true);
-    let parser = rstml::Parser::new(config);
-    let (nodes, errors) = parser.parse_recoverable(tokens).split_vec();
-    let errors = errors.into_iter().map(|e| e.emit_as_expr_tokens());
-    let nodes_output = view::render_view(&nodes);
-    quote! {
-        {
-            #(#errors;)*
-            #nodes_output
+        let config = rstml::ParserConfig::default().recover_block(true);
+        let parser = rstml::Parser::new(config);
+        let (nodes, errors) = parser.parse_recoverable(tokens).split_vec();
+        let errors = errors.into_iter().map(|e| e.emit_as_expr_tokens());
+        let nodes_output = include::within(&file, false, || view::render_view(&nodes));
+        let file = file.to_string_lossy();
+        quote! {
+            {
+                // Rebuild when the template changes.
+                const _: &str = ::core::include_str!(#file);
+                #(#errors;)*
+                #nodes_output
+            }
         }
-    }
-    .into()
+        .into()
+    })
+}
+
+/// Turns a function into a component that can be used as a tag in `view!`.
+///
+/// The function's arguments become the fields of a generated props struct,
+/// named after the function with a `Props` suffix, together with a builder
+/// that fails to compile if a required prop is missing. Arguments can be
+/// marked with `#[prop(default)]` or `#[prop(default = expr)]` to make them
+/// optional, and `#[prop(into)]` to accept anything that converts into the
+/// argument's type.
+#[proc_macro_error::proc_macro_error]
+#[proc_macro_attribute]
+pub fn component(
+    _args: proc_macro::TokenStream,
+    tokens: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    let item = syn::parse_macro_input!(tokens as syn::ItemFn);
+    component::component_to_tokens(item).into()
 }
@@ -0,0 +1,335 @@
+use mrml::prelude::parser::memory_loader::MemoryIncludeLoader;
+use mrml::prelude::parser::ParserOptions;
+use mrmx_markup::parse::{self, is_raw_text_element, Attribute, Element, Node, Position, Text};
+use proc_macro2::{Punct, Spacing, Span, TokenStream, TokenTree};
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::parse::Parser;
+use syn::{Expr, Ident, LitStr};
+
+use crate::diagnostic::MarkupError;
+use crate::view::{check_tag, is_mjml_element};
+use crate::{attributes, nesting, urls};
+
+/// Whether whitespace-only text in `<tag>` is significant. Indentation between
+/// the children of other MJML elements, or of the document, is dropped.
+fn keeps_whitespace(tag: &str) -> bool {
+    if !tag.starts_with("mj") {
+        return !tag.is_empty();
+    }
+    // Keep list alphabetized for binary search
+    [
+        "mj-accordion-text",
+        "mj-accordion-title",
+        "mj-button",
+        "mj-html-attribute",
+        "mj-navbar-link",
+        "mj-preview",
+        "mj-raw",
+        "mj-social-element",
+        "mj-style",
+        "mj-table",
+        "mj-text",
+        "mj-title",
+    ]
+    .binary_search(&tag)
+    .is_ok()
+}
+
+/// Whether `text` in `<parent>` is dropped as indentation.
+fn is_indentation(text: &Text, parent: &str) -> bool {
+    text.value.trim().is_empty() && !keeps_whitespace(parent)
+}
+
+/// A text or attribute value from the template.
+enum Value {
+    Literal(String),
+    /// A Rust expression formatting the value's placeholders.
+    Format(TokenStream),
+}
+
+/// Translates an MJML template into the markup accepted by `view!`, so that
+/// it expands to the same code. Text becomes string literals, and text or
+/// attribute values containing `{expr}` placeholders become `format!` blocks.
+///
+/// The template goes through the same checks as `view!` first, reporting
+/// errors at their line and column in `path`. A whole `<mjml>` document is
+/// also read with mrml's parser.
+pub(crate) fn mjml_to_view(source: &str, path: &str, span: Span) -> TokenStream {
+    let template = Template {
+        source,
+        path,
+        span,
+        placeholders: true,
+    };
+    let nodes = template.parse();
+    template.check(&nodes, "");
+    proc_macro_error::abort_if_dirty();
+    template.check_with_mrml(&nodes, None);
+    template.nodes(&nodes, "")
+}
+
+/// An MJML template or partial, translated into `view!` markup.
+pub(crate) struct Template<'a> {
+    pub(crate) source: &'a str,
+    /// The path of the file, for errors.
+    pub(crate) path: &'a str,
+    /// The span of the path in the macro input, where every error is reported.
+    pub(crate) span: Span,
+    /// Whether `{expr}` placeholders are filled, rather than kept as text.
+    pub(crate) placeholders: bool,
+}
+
+impl<'a> Template<'a> {
+    pub(crate) fn parse(&self) -> Vec<Node<'a>> {
+        match parse::parse(self.source) {
+            Ok(nodes) => nodes,
+            Err(err) => self.abort(err.span.start, err.message),
+        }
+    }
+
+    /// Where `offset` is in the template, like `templates/welcome.mjml:4:5`.
+    fn location(&self, offset: usize) -> String {
+        format!("{}:{}", self.path, Position::of(self.source, offset))
+    }
+
+    fn emit(&self, err: MarkupError, at: parse::Span) {
+        err.emit_at(self.span, &self.location(at.start));
+    }
+
+    fn abort(&self, offset: usize, message: impl std::fmt::Display) -> ! {
+        proc_macro_error::abort!(self.span, "{}: {}", self.location(offset), message)
+    }
+
+    /// Runs the checks of `view!` on `nodes`, the children of `<parent>`.
+    pub(crate) fn check(&self, nodes: &[Node], parent: &str) {
+        let mut seen = Vec::new();
+        for node in nodes {
+            match node {
+                Node::Element(element) => self.check_element(element, parent, &mut seen),
+                Node::Text(text) if !is_indentation(text, parent) => {
+                    if let Err(err) = nesting::check_child(parent, nesting::Child::Text) {
+                        self.emit(err, text.span);
+                    }
+                }
+                Node::Text(_) | Node::Comment(_) => {}
+            }
+        }
+    }
+
+    fn check_element(&self, element: &Element, parent: &str, seen: &mut Vec<String>) {
+        let tag = element.tag;
+        // The children of `<mj-attributes>` hold default attributes, under the
+        // name of the element they apply to.
+        let in_attributes = parent == "mj-attributes";
+        let result = nesting::check_child(parent, nesting::Child::Element(tag))
+            .and_then(|()| nesting::check_unique_child(parent, tag, seen));
+        let result = match result {
+            Ok(()) if !in_attributes => check_tag(tag),
+            result => result,
+        };
+        if let Err(err) = result {
+            self.emit(err, element.span);
+        }
+        for attribute in &element.attributes {
+            let checked = in_attributes || is_mjml_element(tag);
+            if let Err((err, at)) = self.check_attribute(tag, attribute, checked) {
+                self.emit(err, at);
+            }
+        }
+        self.check(&element.children, tag);
+    }
+
+    /// Checks `attribute` of `<tag>`, and its name and syntax if `checked`.
+    fn check_attribute(
+        &self,
+        tag: &str,
+        attribute: &Attribute,
+        checked: bool,
+    ) -> Result<(), (MarkupError, parse::Span)> {
+        if checked {
+            attributes::check_attribute(tag, attribute.name)
+                .map_err(|err| (err, attribute.span))?;
+        }
+        // Values with placeholders are only known at runtime.
+        let Some(value) = attribute.value else {
+            return Ok(());
+        };
+        let Value::Literal(literal) = self.interpolate(value) else {
+            return Ok(());
+        };
+        if checked {
+            attributes::check_value(attribute.name, &literal).map_err(|err| (err, value.span))?;
+        }
+        urls::check_url(tag, attribute.name, &literal).map_err(|err| (err, value.span))
+    }
+
+    /// Reads the file with mrml's parser, so that it is rejected here if mrml
+    /// would reject it at runtime. A partial that is not a whole `<mjml>`
+    /// document is read as the children of `<section>`, the way mrml reads an
+    /// included file, while such a template is not read, since it could belong
+    /// anywhere in a document. The files it includes are checked on their own,
+    /// and stand for empty partials.
+    pub(crate) fn check_with_mrml(&self, nodes: &[Node], section: Option<&str>) {
+        let is_document = nodes
+            .iter()
+            .any(|node| matches!(node, Node::Element(element) if element.tag == "mjml"));
+        let source = match section {
+            _ if is_document => self.source.to_string(),
+            Some(section) => format!("<mjml><{section}>{}</{section}></mjml>", self.source),
+            None => return,
+        };
+        let mut includes = Vec::new();
+        include_paths(nodes, &mut includes);
+        let options = ParserOptions {
+            include_loader: Box::new(MemoryIncludeLoader::from(
+                includes
+                    .into_iter()
+                    .map(|path| (path, ""))
+                    .collect::<Vec<_>>(),
+            )),
+        };
+        if let Err(err) = mrml::parse_with_options(source, &options) {
+            proc_macro_error::abort!(self.span, "{}: invalid MJML: {}", self.path, err)
+        }
+    }
+
+    /// The `view!` markup for `nodes`, the children of `<parent>`.
+    pub(crate) fn nodes(&self, nodes: &[Node], parent: &str) -> TokenStream {
+        nodes.iter().map(|node| self.node(node, parent)).collect()
+    }
+
+    fn node(&self, node: &Node, parent: &str) -> TokenStream {
+        match node {
+            Node::Element(element) => self.element(element),
+            Node::Comment(comment) => {
+                let comment = LitStr::new(comment.value.trim(), self.span);
+                quote! { <!-- #comment --> }
+            }
+            Node::Text(text) if is_indentation(text, parent) => TokenStream::new(),
+            Node::Text(text) if is_raw_text_element(parent) => {
+                let text = LitStr::new(text.value, self.span);
+                quote! { #text }
+            }
+            Node::Text(text) => match self.interpolate(*text) {
+                Value::Literal(text) => {
+                    let text = LitStr::new(&text, self.span);
+                    quote! { #text }
+                }
+                // Interpolated values are escaped, like blocks in `view!`.
+                Value::Format(format) => quote! { { #format } },
+            },
+        }
+    }
+
+    fn element(&self, element: &Element) -> TokenStream {
+        let name = self.name(element.tag, element.span);
+        let attributes = element.attributes.iter().map(|attribute| {
+            let key = self.name(attribute.name, attribute.span);
+            match attribute.value.map(|value| self.interpolate(value)) {
+                None => key,
+                Some(Value::Literal(value)) => {
+                    let value = LitStr::new(&value, self.span);
+                    quote! { #key = #value }
+                }
+                Some(Value::Format(format)) => quote! { #key = { #format } },
+            }
+        });
+        if element.children.is_empty() {
+            return quote! { <#name #(#attributes)* /> };
+        }
+        let children = self.nodes(&element.children, element.tag);
+        quote! { <#name #(#attributes)*> #children </#name> }
+    }
+
+    /// The tokens of a tag or attribute name, like `mj-text` or `xlink:href`.
+    fn name(&self, name: &str, at: parse::Span) -> TokenStream {
+        let mut tokens = Vec::new();
+        let mut rest = name;
+        loop {
+            let end = rest.find(['-', ':']).unwrap_or(rest.len());
+            let mut ident = match Ident::parse_any.parse_str(&rest[..end]) {
+                Ok(ident) => ident,
+                Err(_) => self.abort(at.start, format!("`{name}` cannot be used as a name")),
+            };
+            ident.set_span(self.span);
+            tokens.push(TokenTree::Ident(ident));
+            let Some(separator) = rest[end..].chars().next() else {
+                return tokens.into_iter().collect();
+            };
+            let mut punct = Punct::new(separator, Spacing::Alone);
+            punct.set_span(self.span);
+            tokens.push(TokenTree::Punct(punct));
+            rest = &rest[end + 1..];
+        }
+    }
+
+    /// Splits `text` into literal parts and `{expr}` placeholders, where `{{`
+    /// and `}}` stand for literal braces. A value that is a single placeholder
+    /// is used as is, so that it can be optional like in `view!`.
+    fn interpolate(&self, text: Text) -> Value {
+        if !self.placeholders {
+            return Value::Literal(text.value.to_string());
+        }
+        let mut format = String::new();
+        let mut args = Vec::new();
+        let mut rest = text.value;
+        while let Some(start) = rest.find(['{', '}']) {
+            format.push_str(&rest[..start]);
+            let brace = &rest[start..];
+            let offset = text.span.end - brace.len();
+            if brace.starts_with("{{") || brace.starts_with("}}") {
+                format.push_str(&brace[..2]);
+                rest = &brace[2..];
+                continue;
+            }
+            if brace.starts_with('}') {
+                self.abort(offset, "unmatched `}`")
+            }
+            // The placeholder ends at the first `}` that closes an expression,
+            // so that braces in the expression, like `{format!("{}", x)}`, do
+            // not end it.
+            let mut ends = brace.match_indices('}').map(|(end, _)| end).peekable();
+            let Some(&first) = ends.peek() else {
+                self.abort(offset, "unterminated placeholder")
+            };
+            let parse = |end: usize| syn::parse_str::<Expr>(brace[1..end].trim());
+            let Some((end, expr)) = ends.find_map(|end| Some((end, parse(end).ok()?))) else {
+                let expr = brace[1..first].trim();
+                let err = parse(first).unwrap_err();
+                self.abort(offset, format!("invalid placeholder `{{{expr}}}`: {err}"))
+            };
+            args.push(expr);
+            format.push_str("{}");
+            rest = &brace[end + 1..];
+        }
+        format.push_str(rest);
+        match args.as_slice() {
+            [] => Value::Literal(format.replace("{{", "{").replace("}}", "}")),
+            [expr] if format == "{}" => Value::Format(quote! { #expr }),
+            args => {
+                let format = LitStr::new(&format, self.span);
+                Value::Format(quote! { ::std::format!(#format, #(#args),*) })
+            }
+        }
+    }
+}
+
+/// The paths of the `<mj-include>` elements in `nodes`.
+fn include_paths<'a>(nodes: &[Node<'a>], paths: &mut Vec<&'a str>) {
+    for node in nodes {
+        let Node::Element(element) = node else {
+            continue;
+        };
+        if element.tag == "mj-include" {
+            paths.extend(
+                element
+                    .attributes
+                    .iter()
+                    .filter(|attribute| attribute.name == "path")
+                    .filter_map(|attribute| Some(attribute.value?.value)),
+            );
+        }
+        include_paths(&element.children, paths);
+    }
+}
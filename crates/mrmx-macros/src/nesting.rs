@@ -0,0 +1,218 @@
+use rstml::node::Node;
+use syn::spanned::Spanned;
+
+use crate::diagnostic::MarkupError;
+use crate::view::{component_path, is_mjml_element, CustomChildren};
+
+/// What an MJML element accepts as children, mirroring the `WithChildren`
+/// impls in `mrmx`.
+enum ContentModel {
+    /// Body components, HTML and text.
+    Body,
+    /// Only the listed MJML elements.
+    Only(&'static [&'static str]),
+    /// HTML and text, but no MJML elements.
+    Raw,
+    /// Only text.
+    Text,
+    /// No children at all.
+    Empty,
+}
+
+// Keep list alphabetized
+const BODY_ELEMENTS: &[&str] = &[
+    "mj-accordion",
+    "mj-button",
+    "mj-carousel",
+    "mj-column",
+    "mj-divider",
+    "mj-group",
+    "mj-hero",
+    "mj-image",
+    "mj-include",
+    "mj-navbar",
+    "mj-raw",
+    "mj-section",
+    "mj-social",
+    "mj-spacer",
+    "mj-table",
+    "mj-text",
+    "mj-wrapper",
+];
+
+fn content_model(tag: &str) -> Option<ContentModel> {
+    use ContentModel::*;
+    Some(match tag {
+        "mjml" => Only(&["mj-body", "mj-head"]),
+        "mj-head" => Only(&[
+            "mj-attributes",
+            "mj-breakpoint",
+            "mj-font",
+            "mj-html-attributes",
+            "mj-include",
+            "mj-preview",
+            "mj-raw",
+            "mj-style",
+            "mj-title",
+        ]),
+        "mj-body" | "mj-button" | "mj-column" | "mj-group" | "mj-hero" | "mj-section"
+        | "mj-table" | "mj-text" | "mj-wrapper" => Body,
+        "mj-accordion" => Only(&["mj-accordion-element"]),
+        "mj-accordion-element" => Only(&["mj-accordion-text", "mj-accordion-title"]),
+        "mj-accordion-text" | "mj-navbar-link" | "mj-raw" | "mj-social-element" => Raw,
+        "mj-accordion-title" => Text,
+        "mj-carousel" => Only(&["mj-carousel-image"]),
+        "mj-html-attributes" => Only(&["mj-selector"]),
+        "mj-html-attribute" | "mj-preview" | "mj-style" | "mj-title" => Text,
+        "mj-navbar" => Only(&["mj-navbar-link"]),
+        "mj-selector" => Only(&["mj-html-attribute"]),
+        "mj-social" => Only(&["mj-social-element"]),
+        "mj-breakpoint" | "mj-carousel-image" | "mj-divider" | "mj-font" | "mj-image"
+        | "mj-include" | "mj-spacer" => Empty,
+        _ => return None,
+    })
+}
+
+/// A child of an element, as far as nesting is concerned.
+#[derive(Clone, Copy)]
+pub(crate) enum Child<'a> {
+    Element(&'a str),
+    Text,
+}
+
+/// Emits an error for every child of `<tag>` that it cannot contain, looking
+/// through fragments and control flow. Blocks and components are left to the
+/// type checker.
+pub(crate) fn validate_children<C>(tag: &str, children: &[Node<C>])
+where
+    C: CustomChildren,
+{
+    for child in children {
+        let result = match child {
+            Node::Element(element) if component_path(element.name()).is_some() => Ok(()),
+            Node::Element(element) => {
+                let name = element.name().to_string();
+                check_child(tag, Child::Element(&name)).map_err(|err| (err, element.name().span()))
+            }
+            Node::Text(_) | Node::RawText(_) => {
+                check_child(tag, Child::Text).map_err(|err| (err, child.span()))
+            }
+            Node::Fragment(fragment) => {
+                validate_children(tag, &fragment.children);
+                Ok(())
+            }
+            Node::Custom(custom) => {
+                for nodes in custom.bodies() {
+                    validate_children(tag, nodes);
+                }
+                Ok(())
+            }
+            Node::Block(_) | Node::Comment(_) | Node::Doctype(_) => Ok(()),
+        };
+        if let Err((err, span)) = result {
+            err.emit(span);
+        }
+    }
+}
+
+/// Checks that `<tag>` can contain `child`.
+pub(crate) fn check_child(tag: &str, child: Child) -> Result<(), MarkupError> {
+    let Some(model) = content_model(tag) else {
+        return Ok(());
+    };
+    let allowed = match (&model, child) {
+        (ContentModel::Empty, _) => {
+            return Err(MarkupError::new(format!("<{tag}> cannot have children")))
+        }
+        (ContentModel::Body | ContentModel::Raw | ContentModel::Text, Child::Text) => true,
+        (ContentModel::Only(_), Child::Text) => {
+            return Err(MarkupError::new(format!("<{tag}> cannot contain text"))
+                .help(allowed_help(tag, &model)))
+        }
+        (ContentModel::Body, Child::Element(child)) => {
+            !is_mjml_element(child) || BODY_ELEMENTS.contains(&child)
+        }
+        (ContentModel::Only(elements), Child::Element(child)) => elements.contains(&child),
+        (ContentModel::Raw, Child::Element(child)) => !is_mjml_element(child),
+        (ContentModel::Text, Child::Element(_)) => false,
+    };
+    match child {
+        Child::Element(child) if !allowed => Err(MarkupError::new(format!(
+            "<{child}> cannot be a child of <{tag}>"
+        ))
+        .help(allowed_help(tag, &model))),
+        _ => Ok(()),
+    }
+}
+
+/// Emits an error for a second `<mj-accordion-title>` or `<mj-accordion-text>`
+/// in an `<mj-accordion-element>`, which has room for only one of each. Those
+/// coming from blocks or control flow are merged at runtime instead.
+pub(crate) fn validate_unique_children<C>(tag: &str, children: &[Node<C>])
+where
+    C: CustomChildren,
+{
+    let mut seen = Vec::new();
+    check_unique(tag, children, &mut seen);
+}
+
+fn check_unique<C>(tag: &str, children: &[Node<C>], seen: &mut Vec<String>)
+where
+    C: CustomChildren,
+{
+    for child in children {
+        match child {
+            Node::Element(element) => {
+                let child = element.name().to_string();
+                if let Err(err) = check_unique_child(tag, &child, seen) {
+                    err.emit(element.name().span());
+                }
+            }
+            Node::Fragment(fragment) => check_unique(tag, &fragment.children, seen),
+            _ => {}
+        }
+    }
+}
+
+/// Checks that `child` is not a second title or text of an
+/// `<mj-accordion-element>`, given the children `seen` before it.
+pub(crate) fn check_unique_child(
+    tag: &str,
+    child: &str,
+    seen: &mut Vec<String>,
+) -> Result<(), MarkupError> {
+    if tag != "mj-accordion-element" || !matches!(child, "mj-accordion-title" | "mj-accordion-text")
+    {
+        return Ok(());
+    }
+    if seen.iter().any(|seen| seen == child) {
+        return Err(
+            MarkupError::new(format!("<{tag}> can only contain one <{child}>"))
+                .help(format!("move this content into the first <{child}>")),
+        );
+    }
+    seen.push(child.to_string());
+    Ok(())
+}
+
+fn allowed_help(tag: &str, model: &ContentModel) -> String {
+    match model {
+        ContentModel::Body => format!("<{tag}> accepts HTML, text and {}", tag_list(BODY_ELEMENTS)),
+        ContentModel::Only(elements) => format!("<{tag}> accepts {}", tag_list(elements)),
+        ContentModel::Raw => format!("<{tag}> accepts HTML and text, but no mj-* elements"),
+        ContentModel::Text => format!("<{tag}> accepts only text"),
+        ContentModel::Empty => format!("<{tag}> cannot have children"),
+    }
+}
+
+fn tag_list(tags: &[&str]) -> String {
+    let tags = tags
+        .iter()
+        .map(|tag| format!("<{tag}>"))
+        .collect::<Vec<_>>();
+    match tags.split_last() {
+        Some((last, [])) => last.clone(),
+        Some((last, rest)) => format!("{} or {last}", rest.join(", ")),
+        None => String::new(),
+    }
+}
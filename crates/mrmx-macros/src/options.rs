@@ -0,0 +1,92 @@
+use std::cell::RefCell;
+
+use mrmx_markup::url::UrlPolicy;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{Attribute, LitStr, Token};
+
+thread_local! {
+    /// The options of the macro being expanded.
+    static OPTIONS: RefCell<Options> = RefCell::new(Options::default());
+}
+
+/// Options given as inner attributes at the start of a macro's input, like
+/// `#![custom_tags("mj-chart")]`.
+#[derive(Default)]
+pub(crate) struct Options {
+    /// Tags starting with `mj-` that are rendered as plain nodes.
+    custom_tags: Vec<String>,
+    /// Entries like `mailto:` that loosen the policy for literal URLs.
+    allowed_urls: Vec<String>,
+}
+
+/// The input of a macro: its options, then the rest of it.
+pub(crate) struct WithOptions<T> {
+    pub(crate) options: Options,
+    pub(crate) input: T,
+}
+
+impl<T: Parse> Parse for WithOptions<T> {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut options = Options::default();
+        for attr in input.call(Attribute::parse_inner)? {
+            let list = || attr.parse_args_with(Punctuated::<LitStr, Token![,]>::parse_terminated);
+            if attr.path().is_ident("custom_tags") {
+                for tag in list()? {
+                    if !tag.value().starts_with("mj-") {
+                        return Err(syn::Error::new(
+                            tag.span(),
+                            "only tags starting with `mj-` need to be registered",
+                        ));
+                    }
+                    options.custom_tags.push(tag.value());
+                }
+            } else if attr.path().is_ident("allowed_urls") {
+                for entry in list()? {
+                    if !UrlPolicy::default().allow(&entry.value()) {
+                        return Err(syn::Error::new(
+                            entry.span(),
+                            "expected a scheme like `mailto:`, `relative` or `private-hosts`",
+                        ));
+                    }
+                    options.allowed_urls.push(entry.value());
+                }
+            } else {
+                return Err(syn::Error::new_spanned(
+                    attr.path(),
+                    "unknown option, expected `custom_tags` or `allowed_urls`",
+                ));
+            }
+        }
+        Ok(WithOptions {
+            options,
+            input: input.parse()?,
+        })
+    }
+}
+
+impl<T> WithOptions<T> {
+    /// Runs `f` on the input, with the options in effect.
+    pub(crate) fn expand<R>(self, f: impl FnOnce(T) -> R) -> R {
+        struct Restore(Options);
+
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                OPTIONS.set(std::mem::take(&mut self.0));
+            }
+        }
+
+        let _restore = Restore(OPTIONS.replace(self.options));
+        f(self.input)
+    }
+}
+
+/// Whether `tag` is registered with `#![custom_tags(..)]`.
+pub(crate) fn is_custom_tag(tag: &str) -> bool {
+    OPTIONS.with_borrow(|options| options.custom_tags.iter().any(|custom| custom == tag))
+}
+
+/// The entries of `#![allowed_urls(..)]`.
+pub(crate) fn allowed_urls() -> Vec<String> {
+    OPTIONS.with_borrow(|options| options.allowed_urls.clone())
+}
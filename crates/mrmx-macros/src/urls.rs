@@ -0,0 +1,55 @@
+pub(crate) use mrmx_markup::url::ALLOWED_URLS_VAR;
+use mrmx_markup::url::{decode, is_url_attribute, UrlPolicy, UrlProblem};
+use rstml::node::KeyedAttribute;
+use syn::{Expr, ExprLit, Lit};
+
+use crate::diagnostic::MarkupError;
+use crate::options;
+
+/// Emits an error if `attr` is a URL attribute whose literal value is not
+/// allowed, returning whether the value was checked.
+pub(crate) fn validate_url(tag: &str, attr: &KeyedAttribute) -> bool {
+    let key = attr.key.to_string();
+    if !is_url_attribute(&key) {
+        return false;
+    }
+    let Some(Expr::Lit(ExprLit {
+        lit: Lit::Str(lit), ..
+    })) = attr.value()
+    else {
+        return false;
+    };
+    if let Err(err) = check_url(tag, &key, &lit.value()) {
+        err.emit(lit.span());
+    }
+    true
+}
+
+/// Checks the literal `url` of the attribute `key` of `<tag>`, if it holds a
+/// URL, against the policy loosened by `#![allowed_urls(..)]` and
+/// [`ALLOWED_URLS_VAR`].
+pub(crate) fn check_url(tag: &str, key: &str, url: &str) -> Result<(), MarkupError> {
+    if !is_url_attribute(key) {
+        return Ok(());
+    }
+    let mut policy = UrlPolicy::allowing(&std::env::var(ALLOWED_URLS_VAR).unwrap_or_default());
+    for entry in options::allowed_urls() {
+        policy.allow(&entry);
+    }
+    let problem = match policy.check(&decode(url)) {
+        Ok(()) => return Ok(()),
+        Err(problem) => problem,
+    };
+    let error = MarkupError::new(format!("unsafe `{key}` on <{tag}>: {problem}"));
+    let (fix, entry) = match &problem {
+        UrlProblem::Relative => ("use an absolute `https://` URL", "relative".to_string()),
+        UrlProblem::Scheme(scheme) if matches!(&**scheme, "javascript" | "data" | "vbscript") => {
+            return Err(error)
+        }
+        UrlProblem::Scheme(scheme) => ("use an `https://` URL", format!("{scheme}:")),
+        UrlProblem::PrivateHost(_) => ("link to a public host", "private-hosts".to_string()),
+    };
+    Err(error.help(format!(
+        "{fix}, or allow it with `#![allowed_urls(\"{entry}\")]` or in `{ALLOWED_URLS_VAR}`"
+    )))
+}
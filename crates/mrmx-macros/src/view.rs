@@ -1,59 +1,153 @@
 use convert_case::Case::{Pascal, Snake};
 use convert_case::Casing;
 use proc_macro2::{Ident, Span, TokenStream};
+use proc_macro_crate::FoundCrate;
 use quote::{quote, quote_spanned, ToTokens};
-use rstml::node::{CustomNode, KeyedAttribute, Node, NodeAttribute, NodeElement, NodeName};
+use rstml::node::{
+    CustomNode, KeyedAttribute, Node, NodeAttribute, NodeBlock, NodeElement, NodeName,
+};
 use syn::spanned::Spanned;
-use syn::{parse_quote, LitStr};
+use syn::{Block, Expr, ExprPath, ExprRange, LitStr, RangeLimits, Stmt};
 
-pub fn render_view(nodes: &[Node]) -> Option<TokenStream> {
-    match nodes.len() {
-        0 => {
+use crate::diagnostic::MarkupError;
+use crate::suggest::did_you_mean;
+use crate::urls::ALLOWED_URLS_VAR;
+use crate::{attributes, include, nesting, options, urls};
+
+pub fn render_view<C>(nodes: &[Node<C>]) -> Option<TokenStream>
+where
+    C: CustomChildren,
+{
+    match nodes {
+        [] => {
             let span = Span::call_site();
             Some(quote_spanned! {
                 span => ()
             })
         }
-        1 => node_to_tokens(&nodes[0], TagType::Root),
+        // A lone block is the value of the whole view, as is.
+        [Node::Block(block)] if spread_expr(block).is_none() => Some(quote! { #block }),
+        [node] if !is_many(node) => node_to_tokens(node, TagType::Root).map(Child::into_tokens),
         _ => fragment_to_tokens(nodes, TagType::Root),
     }
 }
 
-fn element_children_to_tokens<C>(nodes: &[Node<C>], parent_type: TagType) -> Option<TokenStream>
+/// A custom node that expands to any number of children.
+pub(crate) trait CustomChildren: CustomNode {
+    /// Returns statements that push each child onto [`children_ident`].
+    fn children_to_tokens(&self, parent_type: TagType) -> TokenStream;
+
+    /// Returns the markup of every branch or body.
+    fn bodies(&self) -> Vec<&[Node<Self>]>
+    where
+        Self: Sized;
+}
+
+impl CustomChildren for rstml::Infallible {
+    fn children_to_tokens(&self, _parent_type: TagType) -> TokenStream {
+        unreachable!("Infallible is never parsed")
+    }
+
+    fn bodies(&self) -> Vec<&[Node<Self>]> {
+        unreachable!("Infallible is never parsed")
+    }
+}
+
+/// A child of an element, as either a single expression, or statements that
+/// push any number of children onto [`children_ident`].
+pub(crate) enum Child {
+    Single(TokenStream),
+    Many(TokenStream),
+}
+
+impl Child {
+    fn into_tokens(self) -> TokenStream {
+        match self {
+            Child::Single(tt) | Child::Many(tt) => tt,
+        }
+    }
+}
+
+/// The vector that children are collected into when some of them are not
+/// known until runtime. It is hygienic, so it does not clash with user code.
+pub(crate) fn children_ident() -> Ident {
+    Ident::new("children", Span::mixed_site())
+}
+
+fn is_many<C>(node: &Node<C>) -> bool
 where
-    C: rstml::node::CustomNode,
+    C: CustomChildren,
 {
-    let children = children_to_tokens(nodes, parent_type)
-        .into_iter()
-        .map(|child| {
-            quote! {
-                #child,
-            }
+    match node {
+        Node::Custom(_) => true,
+        Node::Block(block) => spread_expr(block).is_some(),
+        _ => false,
+    }
+}
+
+/// Builds the `Vec` of children passed to `WithChildren::with_children`.
+pub(crate) fn element_children_to_tokens<C>(
+    nodes: &[Node<C>],
+    parent_type: TagType,
+) -> Option<TokenStream>
+where
+    C: CustomChildren,
+{
+    let children = children_to_tokens(nodes, parent_type);
+    if children.is_empty() {
+        return None;
+    }
+    if children
+        .iter()
+        .all(|child| matches!(child, Child::Single(_)))
+    {
+        let children = children.into_iter().map(Child::into_tokens);
+        return Some(quote! {
+            vec![#(#children,)*]
         });
+    }
+
+    let vec = children_ident();
+    let children = push_children(children);
     Some(quote! {
-        #(#children)*
+        {
+            let mut #vec = ::std::vec::Vec::new();
+            #children
+            #vec
+        }
     })
 }
 
+/// Returns statements that push every child onto [`children_ident`].
+pub(crate) fn push_children(children: Vec<Child>) -> TokenStream {
+    let vec = children_ident();
+    let children = children.into_iter().map(|child| match child {
+        Child::Single(child) => quote! { #vec.push(#child); },
+        Child::Many(children) => children,
+    });
+    quote! {
+        #(#children)*
+    }
+}
+
 fn fragment_to_tokens<C>(nodes: &[Node<C>], _parent_type: TagType) -> Option<TokenStream>
 where
-    C: rstml::node::CustomNode,
+    C: CustomChildren,
 {
-    let children = children_to_tokens(nodes, TagType::Fragment);
-    if children.is_empty() {
-        Some(quote! {
-            ::mrml::fragment::Fragment::default()
-        })
-    } else {
-        Some(quote! {
-            ::mrml::fragment::Fragment::from(vec![#(#children),*])
-        })
+    let mrmx = mrmx_path();
+    match element_children_to_tokens(nodes, TagType::Fragment) {
+        Some(children) => Some(quote! {
+            #mrmx::mrml::fragment::Fragment::from(#children)
+        }),
+        None => Some(quote! {
+            #mrmx::mrml::fragment::Fragment::default()
+        }),
     }
 }
 
-fn children_to_tokens<C>(nodes: &[Node<C>], parent_type: TagType) -> Vec<TokenStream>
+pub(crate) fn children_to_tokens<C>(nodes: &[Node<C>], parent_type: TagType) -> Vec<Child>
 where
-    C: rstml::node::CustomNode,
+    C: CustomChildren,
 {
     let nodes = nodes
         .iter()
@@ -69,15 +163,61 @@ pub(crate) enum TagType {
     Mjml,
     Html,
     MjmlAttributes,
+    MjmlHead,
+    /// An MJML element that only holds text nodes, like
+    /// `<mj-accordion-title>`.
+    MjmlText,
+    Component,
 }
 
-fn node_to_tokens<C>(node: &Node<C>, parent_type: TagType) -> Option<TokenStream>
+fn node_to_tokens<C>(node: &Node<C>, parent_type: TagType) -> Option<Child>
 where
-    C: rstml::node::CustomNode,
+    C: CustomChildren,
 {
+    if let Node::Custom(custom) = node {
+        return Some(Child::Many(custom.children_to_tokens(parent_type)));
+    }
+    if let Node::Element(element) = node {
+        if element.name().to_string() == "mj-include" && parent_type != TagType::MjmlAttributes {
+            nesting::validate_children("mj-include", &element.children);
+            return Some(include::include_to_tokens(element, parent_type));
+        }
+    }
+    if let Node::Block(block) = node {
+        if let Some(items) = spread_expr(block) {
+            let vec = children_ident();
+            let child = Ident::new("child", Span::mixed_site());
+            return Some(Child::Many(quote! {
+                for #child in #items {
+                    #vec.push(::core::convert::Into::into(#child));
+                }
+            }));
+        }
+        if parent_type == TagType::MjmlText && !is_explicit_into(block) {
+            let vec = children_ident();
+            let mrmx = mrmx_path();
+            return Some(Child::Many(quote! {
+                #[allow(unused_braces)]
+                #mrmx::escape::into_text_children(#block, &mut #vec);
+            }));
+        }
+        if parent_type != TagType::Root && !is_explicit_into(block) {
+            let vec = children_ident();
+            let mrmx = mrmx_path();
+            return Some(Child::Many(quote! {
+                #[allow(unused_braces)]
+                #mrmx::IntoChildren::into_children(#block, &mut #vec);
+            }));
+        }
+    }
     match node {
         Node::Fragment(fragment) => fragment_to_tokens(&fragment.children, parent_type),
-        Node::Block(block) => Some(quote! { #block }),
+        // The braces of a single expression are removed, as they would be
+        // flagged as unnecessary in `vec![..]`.
+        Node::Block(block) => match block.try_block().map(|block| block.stmts.as_slice()) {
+            Some([Stmt::Expr(expr, None)]) => Some(quote! { #expr }),
+            _ => Some(quote! { #block }),
+        },
         Node::Comment(text) => Some(comment_to_tokens(&text.value)),
         Node::Text(text) => Some(text_to_tokens(&text.value)),
         Node::RawText(raw) => {
@@ -91,22 +231,24 @@ where
         Node::Element(node) => element_to_tokens(node, parent_type),
         _ => None,
     }
-    .map(|tt| -> TokenStream {
+    .map(|tt| -> Child {
         if parent_type == TagType::Root {
-            return tt;
+            return Child::Single(tt);
         }
         match node {
-            Node::Block(_) => tt,
-            _ => quote! {#tt.into()},
+            Node::Block(_) => Child::Single(tt),
+            _ => Child::Single(quote! { ::core::convert::Into::into(#tt) }),
         }
     })
 }
 
 fn text_to_tokens(text: &LitStr) -> TokenStream {
-    quote! { mrml::text::Text::from(#text) }
+    let mrmx = mrmx_path();
+    quote! { #mrmx::mrml::text::Text::from(#text) }
 }
 fn comment_to_tokens(text: &LitStr) -> TokenStream {
-    quote! { mrml::comment::Comment::from(#text) }
+    let mrmx = mrmx_path();
+    quote! { #mrmx::mrml::comment::Comment::from(#text) }
 }
 
 pub(crate) fn element_to_tokens<C>(
@@ -114,74 +256,107 @@ pub(crate) fn element_to_tokens<C>(
     parent_type: TagType,
 ) -> Option<TokenStream>
 where
-    C: CustomNode,
+    C: CustomChildren,
 {
     let tag_type: TagType;
     let name = node.name();
+    if let Some(component) = component_path(name) {
+        return component_to_tokens(component, node);
+    }
     let tag = name.to_string();
+    let mrmx = mrmx_path();
 
+    // The content of a text element mixing text and expressions.
+    let mut text_children = None;
     let name = if parent_type == TagType::MjmlAttributes {
         tag_type = TagType::Mjml;
         if tag == "mj-all" {
-            quote! { ::mrml::mj_attributes_all::MjAttributesAll::default() }
+            quote! { #mrmx::mrml::mj_attributes_all::MjAttributesAll::default() }
         } else if tag == "mj-class" {
-            quote! { ::mrml::mj_attributes_class::MjAttributesClass::default() }
+            quote! { #mrmx::mrml::mj_attributes_class::MjAttributesClass::default() }
         } else {
-            quote! { ::mrml::mj_attributes_element::MjAttributesElement::new(#tag.to_string()) }
+            quote! { #mrmx::mrml::mj_attributes_element::MjAttributesElement::new(::std::string::ToString::to_string(#tag)) }
         }
     } else if is_mjml_element(&tag) {
         if tag == "mj-attributes" {
             tag_type = TagType::MjmlAttributes
+        } else if tag == "mj-head" {
+            tag_type = TagType::MjmlHead
+        } else if tag == "mj-accordion-title" {
+            tag_type = TagType::MjmlText
         } else {
             tag_type = TagType::Mjml
         }
         let snake = Ident::new(&convert_to_snake_case(tag.clone()), name.span());
         let pascal = Ident::new(&convert_to_pascal_case(tag.clone()), name.span());
+        let module = if is_html_attributes_element(&tag) {
+            quote! { #mrmx::html_attributes }
+        } else {
+            quote! { #mrmx::mrml::#snake }
+        };
         if is_mjml_text_element(&tag) {
-            if let Some(Node::Block(block)) = node.children.first() {
-                quote! { #[allow(unused_braces)] ::mrml::#snake::#pascal::from(#block) }
-            } else {
-                let val = node
-                    .children
-                    .iter()
-                    .filter_map(|c| match c {
-                        Node::Comment(_) => None,
-                        Node::Text(t) => Some(t.value_string()),
-                        Node::RawText(t) => Some(match t.to_source_text(false) {
-                            Some(val) => val,
-                            None => t.to_token_stream_string(),
-                        }),
-                        node => proc_macro_error::abort!(
-                            node.span(),
-                            "Non-text nodes are not supported as children of text nodes"
-                        ),
-                    })
-                    .fold(String::new(), |a, b| a + &b);
-
-                quote! { ::mrml::#snake::#pascal::from(#val) }
+            // The title and preview are HTML text, unlike the CSS of
+            // `<mj-style>` and the attribute values of `<mj-html-attribute>`,
+            // which are escaped when applied.
+            let escape = matches!(&*tag, "mj-title" | "mj-preview");
+            let block_text = |block: &NodeBlock| {
+                if escape {
+                    quote! { #mrmx::escape::HtmlText::html_text(&#block) }
+                } else {
+                    quote! { ::std::string::ToString::to_string(&#block) }
+                }
+            };
+            match text_parts(&node.children).as_slice() {
+                [] => quote! { #module::#pascal::from("") },
+                [TextPart::Literal(val)] => quote! { #module::#pascal::from(#val) },
+                [TextPart::Block(block)] if escape => {
+                    let text = block_text(block);
+                    quote! { #[allow(unused_braces)] #module::#pascal::from(#text) }
+                }
+                [TextPart::Block(block)] => {
+                    quote! { #[allow(unused_braces)] #module::#pascal::from(#block) }
+                }
+                parts => {
+                    let parts = parts.iter().map(|part| match part {
+                        TextPart::Literal(val) => quote! { ::std::string::String::from(#val) },
+                        TextPart::Block(block) => block_text(block),
+                    });
+                    text_children = Some(quote! { vec![#(#parts,)*] });
+                    quote! { #module::#pascal::default() }
+                }
             }
         } else {
-            quote! { ::mrml::#snake::#pascal::default() }
+            quote! { #module::#pascal::default() }
+        }
+    } else if tag.starts_with("mj-") {
+        tag_type = TagType::Html;
+        if let Err(err) = check_tag(&tag) {
+            err.emit(name.span());
+        }
+        // Reading the variable makes the compiler rebuild this view when the
+        // registered tags change.
+        quote! {
+            #mrmx::mrml::node::Node::from({
+                let _ = ::core::option_env!(#CUSTOM_TAGS_VAR);
+                #tag
+            })
         }
     } else {
         tag_type = TagType::Html;
-        quote! { ::mrml::node::Node::from(#tag) }
+        quote! { #mrmx::mrml::node::Node::from(#tag) }
     };
 
-    let attributes = node.attributes();
-    let attributes = if attributes.len() == 1 {
-        Some(attribute_to_tokens(&tag, &attributes[0], tag_type))
-    } else {
-        let nodes = attributes
-            .iter()
-            .map(|node| attribute_to_tokens(&tag, node, tag_type));
-        Some(quote! {
-            #(#nodes)*
-        })
-    };
+    let element = node.attributes().iter().fold(name, |element, node| {
+        attribute_to_tokens(&tag, node, tag_type, element)
+    });
+
+    nesting::validate_children(&tag, &node.children);
+    nesting::validate_unique_children(&tag, &node.children);
 
     let self_closing = is_self_closing(node);
-    let children = if !self_closing && !is_mjml_text_element(&tag) {
+    let children = if text_children.is_some() {
+        text_children
+    } else if !self_closing && !is_mjml_text_element(&tag) {
         element_children_to_tokens(node.children.as_slice(), tag_type)
     } else {
         if !is_mjml_text_element(&tag) && !node.children.is_empty() {
@@ -197,106 +372,365 @@ where
         None
     };
 
-    if let Some(children) = children.filter(|c| !c.is_empty()) {
+    if let Some(children) = children {
         Some(quote! {
-            ::mrmx::WithChildren::with_children(
-                #name
-                #attributes,
-                vec![#children]
-            )
+            #mrmx::WithChildren::with_children(#element, #children)
         })
     } else {
-        Some(quote! {
-            #name
-            #attributes
-        })
+        Some(element)
+    }
+}
+
+/// A part of the content of a text element like `<mj-title>`.
+enum TextPart<'a> {
+    Literal(String),
+    /// An expression whose `Display` output is inserted.
+    Block(&'a NodeBlock),
+}
+
+/// Splits the children of a text element into literal text, including
+/// comments, and blocks.
+fn text_parts<C>(children: &[Node<C>]) -> Vec<TextPart<'_>>
+where
+    C: CustomChildren,
+{
+    let mut parts = Vec::new();
+    for child in children {
+        let text = match child {
+            Node::Block(block) => {
+                parts.push(TextPart::Block(block));
+                continue;
+            }
+            Node::Comment(c) => format!("<!--{}-->", c.value.value()),
+            Node::Text(t) => t.value_string(),
+            Node::RawText(t) => match t.to_source_text(false) {
+                Some(val) => val,
+                None => t.to_token_stream_string(),
+            },
+            node => proc_macro_error::abort!(
+                node.span(),
+                "Non-text nodes are not supported as children of text nodes"
+            ),
+        };
+        match parts.last_mut() {
+            Some(TextPart::Literal(last)) => last.push_str(&text),
+            _ => parts.push(TextPart::Literal(text)),
+        }
     }
+    parts
 }
 
-fn attribute_to_tokens(tag_name: &str, node: &NodeAttribute, tag_type: TagType) -> TokenStream {
+/// The path to the `mrmx` crate from the calling crate, which may have renamed
+/// it in its `Cargo.toml`.
+pub(crate) fn mrmx_path() -> TokenStream {
+    match proc_macro_crate::crate_name("mrmx") {
+        Ok(FoundCrate::Name(name)) => {
+            let name = Ident::new(&name, Span::call_site());
+            quote! { ::#name }
+        }
+        // `mrmx` refers to itself as `::mrmx` with `extern crate self`.
+        Ok(FoundCrate::Itself) | Err(_) => quote! { ::mrmx },
+    }
+}
+
+/// Returns the path of a component tag, such as `<OrderSummary />` or
+/// `<emails::Footer />`, whose last segment is PascalCase.
+pub(crate) fn component_path(name: &NodeName) -> Option<&ExprPath> {
+    match name {
+        NodeName::Path(path) => path
+            .path
+            .segments
+            .last()
+            .filter(|segment| segment.ident.to_string().starts_with(char::is_uppercase))
+            .map(|_| path),
+        _ => None,
+    }
+}
+
+fn component_to_tokens<C>(component: &ExprPath, node: &NodeElement<C>) -> Option<TokenStream>
+where
+    C: CustomChildren,
+{
+    let props = node.attributes().iter().map(|attr| match attr {
+        NodeAttribute::Block(block) => proc_macro_error::abort!(
+            block.span(),
+            "Components do not support code blocks as attributes"
+        ),
+        NodeAttribute::Attribute(attr) => {
+            let key = attribute_name(&attr.key);
+            let value = attribute_value(attr);
+            quote! {
+                .#key(#value)
+            }
+        }
+    });
+
+    let children =
+        element_children_to_tokens(node.children.as_slice(), TagType::Component).map(|children| {
+            quote! {
+                .children(#children)
+            }
+        });
+
+    // Missing props are reported on `build`, so point it at the tag.
+    let build = quote_spanned! { component.span() => .build() };
+    let mrmx = mrmx_path();
+    Some(quote! {
+        #component(
+            #mrmx::component_props_builder(&#component)
+                #(#props)*
+                #children
+                #build
+        )
+    })
+}
+
+/// Wraps `element` in the call that sets the attribute `node` on it.
+fn attribute_to_tokens(
+    tag_name: &str,
+    node: &NodeAttribute,
+    tag_type: TagType,
+    element: TokenStream,
+) -> TokenStream {
+    let mrmx = mrmx_path();
     match node {
         NodeAttribute::Block(node) => {
-            proc_macro_error::abort!(
-                node.span(),
-                "Code blocks in attributes are not yet supported"
-            )
+            let Some(attrs) = spread_expr(node) else {
+                proc_macro_error::abort!(
+                    node.span(),
+                    "Code blocks in attributes are not yet supported";
+                    help = "use `{..attrs}` to spread a collection of attributes"
+                )
+            };
+            if tag_type != TagType::Html
+                && (is_mjml_not_free_attributes(tag_name)
+                    || attributes::has_typed_attributes(tag_name))
+            {
+                proc_macro_error::abort!(
+                    node.span(),
+                    format!("<{tag_name}> does not support spread attributes")
+                )
+            }
+            quote! {
+                #mrmx::WithAttribute::with_attributes(
+                    #element,
+                    #mrmx::escape::escape_attributes(#attrs),
+                )
+            }
         }
         NodeAttribute::Attribute(node) => {
+            if tag_type != TagType::Html {
+                attributes::validate_attribute(tag_name, node);
+            }
+            let url_checked = urls::validate_url(tag_name, node);
             if tag_type == TagType::Html || !is_mjml_not_free_attributes(tag_name) {
                 let key = &node.key.to_string();
-                let none = parse_quote! { "" };
-                let value = &node.value().unwrap_or(&none);
-                quote! {
-                    .with_attribute(#key.to_string(), #value.to_string())
+                match node.value() {
+                    // Reading the variable makes the compiler check the URL
+                    // again when the allowed URLs change.
+                    Some(value @ Expr::Lit(_)) if url_checked => quote! {
+                        #mrmx::WithAttribute::with_attribute(
+                            #element,
+                            ::std::string::ToString::to_string(#key),
+                            ::std::string::ToString::to_string(&{
+                                let _ = ::core::option_env!(#ALLOWED_URLS_VAR);
+                                #value
+                            }),
+                        )
+                    },
+                    Some(value @ Expr::Lit(_)) => quote! {
+                        #mrmx::WithAttribute::with_attribute(
+                            #element,
+                            ::std::string::ToString::to_string(#key),
+                            ::std::string::ToString::to_string(&#value),
+                        )
+                    },
+                    Some(value) => {
+                        let value = attribute_expr(value);
+                        let convert = if is_css_attribute(tag_name, key) {
+                            quote! { css_value }
+                        } else {
+                            quote! { attribute_value }
+                        };
+                        quote! {
+                            #mrmx::WithAttribute::with_optional_attribute(
+                                #element,
+                                ::std::string::ToString::to_string(#key),
+                                {
+                                    use #mrmx::{ViaDisplay as _, ViaIntoAttributeValue as _};
+                                    #mrmx::AttributeValue(#value).#convert()
+                                },
+                            )
+                        }
+                    }
+                    None => quote! {
+                        #mrmx::WithAttribute::with_attribute(
+                            #element,
+                            ::std::string::ToString::to_string(#key),
+                            ::std::string::String::new(),
+                        )
+                    },
                 }
             } else {
                 let key = attribute_name(&node.key);
                 let value = attribute_value(node);
                 quote! {
-                    .#key(#value)
+                    #element.#key(#value)
                 }
             }
         }
     }
 }
 
+/// Returns the expression being spread by a `{..attrs}` attribute block or a
+/// `{..children}` child block.
+fn spread_expr(block: &NodeBlock) -> Option<&Expr> {
+    match block.try_block()?.stmts.as_slice() {
+        [Stmt::Expr(
+            Expr::Range(ExprRange {
+                start: None,
+                limits: RangeLimits::HalfOpen(_),
+                end: Some(attrs),
+                ..
+            }),
+            None,
+        )] => Some(attrs),
+        _ => None,
+    }
+}
+
+/// Whether a block already converts its value with `.into()` or
+/// `Into::into(..)` on every path, like `{ title.into() }`. Such blocks are
+/// pushed as a single child, since the target of the conversion could not be
+/// inferred through `IntoChildren`.
+fn is_explicit_into(block: &NodeBlock) -> bool {
+    block
+        .try_block()
+        .and_then(block_tail)
+        .is_some_and(tail_is_into)
+}
+
+fn block_tail(block: &Block) -> Option<&Expr> {
+    match block.stmts.last()? {
+        Stmt::Expr(expr, None) => Some(expr),
+        _ => None,
+    }
+}
+
+fn tail_is_into(expr: &Expr) -> bool {
+    match expr {
+        Expr::MethodCall(call) => call.method == "into" && call.args.is_empty(),
+        // `Into::into(title)`, with any path to the trait.
+        Expr::Call(call) => match &*call.func {
+            Expr::Path(func) if call.args.len() == 1 => {
+                let mut segments = func.path.segments.iter().rev();
+                segments.next().is_some_and(|s| s.ident == "into")
+                    && segments.next().is_some_and(|s| s.ident == "Into")
+            }
+            _ => false,
+        },
+        Expr::Paren(expr) => tail_is_into(&expr.expr),
+        Expr::Block(expr) => block_tail(&expr.block).is_some_and(tail_is_into),
+        Expr::If(expr) => {
+            block_tail(&expr.then_branch).is_some_and(tail_is_into)
+                && expr
+                    .else_branch
+                    .as_ref()
+                    .is_some_and(|(_, expr)| tail_is_into(expr))
+        }
+        Expr::Match(expr) => expr.arms.iter().all(|arm| tail_is_into(&arm.body)),
+        _ => false,
+    }
+}
+
 fn is_self_closing<C>(node: &NodeElement<C>) -> bool
 where
     C: CustomNode,
 {
-    // self-closing tags
-    // https://developer.mozilla.org/en-US/docs/Glossary/Empty_element
-    // Keep list alphabetized for binary search
-    [
-        "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param",
-        "source", "track", "wbr",
-    ]
-    .binary_search(&node.name().to_string().as_str())
-    .is_ok()
+    mrmx_markup::parse::is_void_element(&node.name().to_string())
 }
 
+// Keep list alphabetized for binary search
+const MJML_ELEMENTS: &[&str] = &[
+    "mj-accordion",
+    "mj-accordion-element",
+    "mj-accordion-text",
+    "mj-accordion-title",
+    "mj-attributes",
+    "mj-body",
+    "mj-breakpoint",
+    "mj-button",
+    "mj-carousel",
+    "mj-carousel-image",
+    "mj-column",
+    "mj-divider",
+    "mj-font",
+    "mj-group",
+    "mj-head",
+    "mj-hero",
+    "mj-html-attribute",
+    "mj-html-attributes",
+    "mj-image",
+    "mj-include",
+    "mj-navbar",
+    "mj-navbar-link",
+    "mj-preview",
+    "mj-raw",
+    "mj-section",
+    "mj-selector",
+    "mj-social",
+    "mj-social-element",
+    "mj-spacer",
+    "mj-style",
+    "mj-table",
+    "mj-text",
+    "mj-title",
+    "mj-wrapper",
+    "mjml",
+];
+
 pub(crate) fn is_mjml_element(tag: &str) -> bool {
-    // Keep list alphabetized for binary search
-    [
-        "mj-accordion",
-        "mj-accordion-element",
-        "mj-accordion-text",
-        "mj-accordion-title",
-        "mj-attributes",
-        "mj-body",
-        "mj-breakpoint",
-        "mj-button",
-        "mj-carousel",
-        "mj-carousel-image",
-        "mj-column",
-        "mj-divider",
-        "mj-font",
-        "mj-group",
-        "mj-head",
-        "mj-hero",
-        "mj-image",
-        "mj-navbar",
-        "mj-navbar-link",
-        "mj-preview",
-        "mj-raw",
-        "mj-section",
-        "mj-social",
-        "mj-social-element",
-        "mj-spacer",
-        "mj-style",
-        "mj-table",
-        "mj-text",
-        "mj-title",
-        "mj-wrapper",
-        "mjml",
-    ]
-    .binary_search(&tag)
-    .is_ok()
+    MJML_ELEMENTS.binary_search(&tag).is_ok()
+}
+
+/// The environment variable listing custom `mj-*` tags, which are rendered as
+/// plain nodes instead of being rejected as typos.
+const CUSTOM_TAGS_VAR: &str = "MRMX_CUSTOM_TAGS";
+
+fn is_custom_tag(tag: &str) -> bool {
+    options::is_custom_tag(tag)
+        || std::env::var(CUSTOM_TAGS_VAR)
+            .is_ok_and(|tags| tags.split(',').any(|custom| custom.trim() == tag))
+}
+
+/// Checks that an `mj-*` tag is an MJML element or a registered custom tag.
+pub(crate) fn check_tag(tag: &str) -> Result<(), MarkupError> {
+    if !tag.starts_with("mj-") || is_mjml_element(tag) || is_custom_tag(tag) {
+        return Ok(());
+    }
+    let err = MarkupError::new(format!("<{tag}> is not an MJML element"));
+    let err = match did_you_mean(tag, MJML_ELEMENTS.iter().copied()) {
+        Some(suggestion) => err.help(format!("did you mean <{suggestion}>?")),
+        None => err,
+    };
+    Err(err.note(format!(
+        "register custom tags with `#![custom_tags(\"{tag}\")]` at the start of the macro input, \
+         or in `{CUSTOM_TAGS_VAR}`"
+    )))
+}
+
+/// Whether `<tag>` is one of the elements mrml lacks, which are built from
+/// `mrmx::html_attributes` instead.
+fn is_html_attributes_element(tag: &str) -> bool {
+    matches!(
+        tag,
+        "mj-html-attribute" | "mj-html-attributes" | "mj-selector"
+    )
 }
 
 fn is_mjml_text_element(tag: &str) -> bool {
     // Keep list alphabetized for binary search
-    ["mj-preview", "mj-style", "mj-title", "mj_preview"]
+    ["mj-html-attribute", "mj-preview", "mj-style", "mj-title"]
         .binary_search(&tag)
         .is_ok()
 }
@@ -309,13 +743,22 @@ fn is_mjml_not_free_attributes(tag: &str) -> bool {
         "mj-include-body",
         "mj-include-head",
         "mj-preview",
-        "mj-raw",
         "mj-title",
     ]
     .binary_search(&tag)
     .is_ok()
 }
 
+/// Whether mrml writes the attribute into CSS rather than HTML, such as the
+/// `href` of `<mj-font>` in an `@import`, so that its value must not be
+/// HTML-escaped.
+fn is_css_attribute(tag: &str, key: &str) -> bool {
+    matches!(
+        (tag, key),
+        ("mj-breakpoint", "width") | ("mj-font", "href" | "name") | ("mj-style", "inline")
+    )
+}
+
 fn attribute_name(name: &NodeName) -> TokenStream {
     let s = name.to_string();
     Ident::new_raw(&s.replace('-', "_"), name.span()).to_token_stream()
@@ -323,13 +766,25 @@ fn attribute_name(name: &NodeName) -> TokenStream {
 
 fn attribute_value(attr: &KeyedAttribute) -> TokenStream {
     match attr.value() {
-        Some(value) => {
-            quote! { #value }
-        }
+        Some(value) => attribute_expr(value),
         None => quote! { true },
     }
 }
 
+/// Removes the braces around a single-expression attribute value, such as
+/// `href={url}`, so that the value is not moved into a block.
+fn attribute_expr(value: &Expr) -> TokenStream {
+    match value {
+        Expr::Block(block) if block.attrs.is_empty() && block.label.is_none() => {
+            match block.block.stmts.as_slice() {
+                [Stmt::Expr(expr, None)] => attribute_expr(expr),
+                _ => quote! { #value },
+            }
+        }
+        value => quote! { #value },
+    }
+}
+
 pub(crate) fn convert_to_snake_case(name: String) -> String {
     if !name.is_case(Snake) {
         name.to_case(Snake)
@@ -0,0 +1,6 @@
+//! Parsing of MJML markup and the policy for URLs in it, shared by `mrmx`, its
+//! macros and the `mrmx` CLI.
+//!
+//! Please see the `mrmx` crate for more information.
+pub mod parse;
+pub mod url;
@@ -0,0 +1,330 @@
+//! A parser for MJML markup that keeps the position of every node, so that
+//! errors in a template point at the right line.
+//!
+//! Text is kept as written, without decoding entities or collapsing
+//! whitespace, and the content of `<mj-style>`, `<style>` and `<script>` is
+//! read verbatim. Documents and fragments are both accepted.
+//!
+//! ```
+//! use mrmx_markup::parse::{parse, Node};
+//!
+//! let source = "<mj-text>\n  Hi <b>there</b>\n</mj-text>";
+//! let nodes = parse(source).unwrap();
+//! let [Node::Element(text)] = nodes.as_slice() else {
+//!     panic!("expected one element");
+//! };
+//! assert_eq!(text.tag, "mj-text");
+//! assert_eq!(text.children.len(), 3);
+//!
+//! let source = "<mj-section>\n<mj-column>\n</mj-section>";
+//! let err = parse(source).unwrap_err();
+//! assert_eq!(err.message, "expected </mj-column>, found </mj-section>");
+//! assert_eq!(err.span.position(source).to_string(), "3:1");
+//! ```
+
+use std::fmt;
+
+/// A range of bytes in the source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    /// The line and column where the span starts in `source`.
+    pub fn position(&self, source: &str) -> Position {
+        Position::of(source, self.start)
+    }
+}
+
+/// A line and column in the source, both starting at 1. Columns count
+/// characters rather than bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Position {
+    /// The position of the byte at `offset` in `source`.
+    pub fn of(source: &str, offset: usize) -> Self {
+        let before = &source[..offset];
+        let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+        Position {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Node<'a> {
+    Element(Element<'a>),
+    Text(Text<'a>),
+    /// A comment, with the text between `<!--` and `-->`.
+    Comment(Text<'a>),
+}
+
+/// A piece of text as written in the source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Text<'a> {
+    pub value: &'a str,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Element<'a> {
+    pub tag: &'a str,
+    /// The span of the tag name in the opening tag.
+    pub span: Span,
+    pub attributes: Vec<Attribute<'a>>,
+    pub children: Vec<Node<'a>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attribute<'a> {
+    pub name: &'a str,
+    /// The span of the name.
+    pub span: Span,
+    /// The value without its quotes, or `None` for a bare attribute like
+    /// `async`.
+    pub value: Option<Text<'a>>,
+}
+
+/// Malformed markup.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    pub span: Span,
+    pub message: String,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Parses an MJML document, or a fragment of one.
+pub fn parse(source: &str) -> Result<Vec<Node<'_>>, Error> {
+    Parser { source, offset: 0 }.nodes(None)
+}
+
+/// Whether `<tag>` is an HTML element that never has children, like `<br>`.
+pub fn is_void_element(tag: &str) -> bool {
+    // https://developer.mozilla.org/en-US/docs/Glossary/Empty_element
+    // Keep list alphabetized for binary search
+    [
+        "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param",
+        "source", "track", "wbr",
+    ]
+    .binary_search(&tag)
+    .is_ok()
+}
+
+/// Whether `<tag>` contains CSS or a script rather than markup.
+pub fn is_raw_text_element(tag: &str) -> bool {
+    matches!(tag, "mj-style" | "script" | "style")
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    offset: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.source[self.offset..]
+    }
+
+    /// Consumes the next `len` bytes, returning them with their span.
+    fn take(&mut self, len: usize) -> Text<'a> {
+        let start = self.offset;
+        self.offset += len;
+        Text {
+            value: &self.source[start..self.offset],
+            span: Span {
+                start,
+                end: self.offset,
+            },
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.offset += rest.len() - rest.trim_start().len();
+    }
+
+    fn error<T>(&self, span: Span, message: String) -> Result<T, Error> {
+        Err(Error { span, message })
+    }
+
+    /// Parses nodes until the closing tag of `parent`.
+    fn nodes(&mut self, parent: Option<(&str, Span)>) -> Result<Vec<Node<'a>>, Error> {
+        let mut nodes = Vec::new();
+        loop {
+            let rest = self.rest();
+            let start = self.offset;
+            if rest.is_empty() {
+                return match parent {
+                    Some((tag, span)) => self.error(span, format!("<{tag}> is never closed")),
+                    None => Ok(nodes),
+                };
+            }
+            if let Some(comment) = rest.strip_prefix("<!--") {
+                let Some(end) = comment.find("-->") else {
+                    let span = self.take(4).span;
+                    return self.error(span, "unterminated comment".to_string());
+                };
+                self.offset += 4;
+                nodes.push(Node::Comment(self.take(end)));
+                self.offset += 3;
+            } else if rest.starts_with("<?") || rest.starts_with("<!") {
+                // XML declarations and doctypes carry nothing for MJML.
+                let Some(end) = rest.find('>') else {
+                    let span = self.take(2).span;
+                    return self.error(span, "unterminated declaration".to_string());
+                };
+                self.offset += end + 1;
+            } else if let Some(close) = rest.strip_prefix("</") {
+                let Some(end) = close.find('>') else {
+                    let span = self.take(2).span;
+                    return self.error(span, "unterminated closing tag".to_string());
+                };
+                let tag = close[..end].trim();
+                let span = Span {
+                    start,
+                    end: start + end + 3,
+                };
+                return match parent {
+                    Some((open, _)) if open == tag => {
+                        self.offset = span.end;
+                        Ok(nodes)
+                    }
+                    Some((open, _)) => {
+                        self.error(span, format!("expected </{open}>, found </{tag}>"))
+                    }
+                    None => self.error(span, format!("unexpected </{tag}>")),
+                };
+            } else if rest.starts_with('<') {
+                nodes.push(Node::Element(self.element()?));
+            } else {
+                let end = rest.find('<').unwrap_or(rest.len());
+                nodes.push(Node::Text(self.take(end)));
+            }
+        }
+    }
+
+    fn element(&mut self) -> Result<Element<'a>, Error> {
+        self.offset += 1;
+        let Text { value: tag, span } = self.name()?;
+        let mut element = Element {
+            tag,
+            span,
+            attributes: Vec::new(),
+            children: Vec::new(),
+        };
+        loop {
+            self.skip_whitespace();
+            let rest = self.rest();
+            if rest.starts_with("/>") {
+                self.offset += 2;
+                return Ok(element);
+            }
+            if rest.starts_with('>') {
+                self.offset += 1;
+                if is_void_element(tag) {
+                    return Ok(element);
+                }
+                element.children = if is_raw_text_element(tag) {
+                    self.raw_text(tag, span)?
+                } else {
+                    self.nodes(Some((tag, span)))?
+                };
+                return Ok(element);
+            }
+            if rest.is_empty() {
+                return self.error(span, format!("<{tag}> is never closed"));
+            }
+            element.attributes.push(self.attribute()?);
+        }
+    }
+
+    /// Parses a tag or attribute name that `view!` can tokenize.
+    fn name(&mut self) -> Result<Text<'a>, Error> {
+        let rest = self.rest();
+        if !rest.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+            let found = rest
+                .chars()
+                .next()
+                .map_or_else(|| "the end of the file".to_string(), |c| format!("`{c}`"));
+            let len = rest.chars().next().map_or(0, char::len_utf8);
+            let span = self.take(len).span;
+            return self.error(span, format!("expected a name, found {found}"));
+        }
+        let end = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':')))
+            .unwrap_or(rest.len());
+        Ok(self.take(end))
+    }
+
+    fn attribute(&mut self) -> Result<Attribute<'a>, Error> {
+        let Text { value: name, span } = self.name()?;
+        self.skip_whitespace();
+        if !self.rest().starts_with('=') {
+            return Ok(Attribute {
+                name,
+                span,
+                value: None,
+            });
+        }
+        self.offset += 1;
+        self.skip_whitespace();
+        let rest = self.rest();
+        let value = match rest.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                let Some(end) = rest[1..].find(quote) else {
+                    return self.error(span, format!("unterminated value for `{name}`"));
+                };
+                self.offset += 1;
+                let value = self.take(end);
+                self.offset += 1;
+                value
+            }
+            _ => {
+                let end = rest
+                    .find(|c: char| c.is_whitespace() || c == '>')
+                    .unwrap_or(rest.len());
+                self.take(end)
+            }
+        };
+        Ok(Attribute {
+            name,
+            span,
+            value: Some(value),
+        })
+    }
+
+    /// Reads the content of `<tag>` verbatim, up to its closing tag.
+    fn raw_text(&mut self, tag: &str, span: Span) -> Result<Vec<Node<'a>>, Error> {
+        let close = format!("</{tag}>");
+        let Some(end) = self.rest().find(&close) else {
+            return self.error(span, format!("<{tag}> is never closed"));
+        };
+        let text = self.take(end);
+        self.offset += close.len();
+        Ok(if text.value.is_empty() {
+            Vec::new()
+        } else {
+            vec![Node::Text(text)]
+        })
+    }
+}
@@ -0,0 +1,237 @@
+//! The policy for URLs in `href`, `src` and similar attributes, shared by the
+//! compile-time checks of `view!` and `mrmx::url_policy`.
+//!
+//! ```
+//! use mrmx_markup::url::{UrlPolicy, UrlProblem};
+//!
+//! let mut policy = UrlPolicy::default();
+//! assert_eq!(
+//!     policy.check("mailto:help@example.com"),
+//!     Err(UrlProblem::Scheme("mailto".to_string())),
+//! );
+//! assert!(policy.allow("mailto:"));
+//! assert_eq!(policy.check("mailto:help@example.com"), Ok(()));
+//! ```
+
+use std::fmt;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+/// The variable holding the entries accepted by [`UrlPolicy::allow`], like
+/// `mailto:, relative`, which loosen the policy of a build.
+pub const ALLOWED_URLS_VAR: &str = "MRMX_ALLOWED_URLS";
+
+/// The URLs accepted in URL attributes, on top of absolute `https://` URLs to
+/// public hosts.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct UrlPolicy {
+    /// Schemes accepted besides `https`, like `mailto` or `http`.
+    pub schemes: Vec<String>,
+    /// Whether URLs without a scheme, like `/unsubscribe`, are accepted.
+    pub allow_relative: bool,
+    /// Whether links to `localhost` and private networks are accepted.
+    pub allow_private_hosts: bool,
+}
+
+impl UrlPolicy {
+    /// The default policy, loosened by the comma-separated entries of
+    /// `allowed`. Entries that [`allow`](UrlPolicy::allow) does not know are
+    /// ignored.
+    pub fn allowing(allowed: &str) -> Self {
+        let mut policy = UrlPolicy::default();
+        for entry in allowed.split(',') {
+            policy.allow(entry.trim());
+        }
+        policy
+    }
+
+    /// Also accepts the URLs named by `entry`: those with a scheme like
+    /// `mailto:`, `relative` URLs, or links to `private-hosts`. Returns `false`
+    /// if the entry is none of these.
+    pub fn allow(&mut self, entry: &str) -> bool {
+        let entry = entry.to_ascii_lowercase();
+        match entry.as_str() {
+            "relative" => self.allow_relative = true,
+            "private-hosts" => self.allow_private_hosts = true,
+            _ => match entry.strip_suffix(':').filter(|scheme| is_scheme(scheme)) {
+                Some(scheme) => self.schemes.push(scheme.to_string()),
+                None => return false,
+            },
+        }
+        true
+    }
+
+    /// Checks a single URL against the policy.
+    ///
+    /// ```
+    /// use mrmx_markup::url::{UrlPolicy, UrlProblem};
+    ///
+    /// let policy = UrlPolicy::default();
+    /// assert_eq!(policy.check("https://example.com/"), Ok(()));
+    /// assert_eq!(
+    ///     policy.check("https://192.168.1.20/"),
+    ///     Err(UrlProblem::PrivateHost("192.168.1.20".to_string())),
+    /// );
+    /// ```
+    pub fn check(&self, url: &str) -> Result<(), UrlProblem> {
+        // Browsers ignore these, so `java\nscript:` is still a `javascript:`
+        // URL.
+        let url = url
+            .trim_matches(|c: char| c <= ' ')
+            .chars()
+            .filter(|c| !matches!(c, '\t' | '\n' | '\r'))
+            .collect::<String>();
+        let Some(scheme) = scheme(&url) else {
+            if self.allow_relative {
+                return Ok(());
+            }
+            return Err(UrlProblem::Relative);
+        };
+        let scheme = scheme.to_ascii_lowercase();
+        if scheme != "https" && !self.schemes.iter().any(|s| s.eq_ignore_ascii_case(&scheme)) {
+            return Err(UrlProblem::Scheme(scheme));
+        }
+        match host(&url[scheme.len() + 1..]) {
+            Some(host) if !self.allow_private_hosts && is_private_host(host) => {
+                Err(UrlProblem::PrivateHost(host.to_string()))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Why a URL is rejected by a [`UrlPolicy`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum UrlProblem {
+    /// The scheme, like `javascript`, is not accepted.
+    Scheme(String),
+    /// The URL has no scheme.
+    Relative,
+    /// The host is `localhost` or on a private network.
+    PrivateHost(String),
+}
+
+impl fmt::Display for UrlProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UrlProblem::Scheme(scheme) => write!(f, "`{scheme}:` URLs are not allowed"),
+            UrlProblem::Relative => write!(f, "relative URLs are not allowed"),
+            UrlProblem::PrivateHost(host) => write!(f, "`{host}` is a local or private host"),
+        }
+    }
+}
+
+impl std::error::Error for UrlProblem {}
+
+/// Whether the attribute `name` holds a URL.
+pub fn is_url_attribute(name: &str) -> bool {
+    matches!(
+        name,
+        "action"
+            | "background"
+            | "background-url"
+            | "formaction"
+            | "href"
+            | "icon-unwrapped-url"
+            | "icon-wrapped-url"
+            | "left-icon"
+            | "poster"
+            | "right-icon"
+            | "src"
+            | "thumbnails-src"
+            | "xlink:href"
+    )
+}
+
+/// Decodes the character references in an attribute value, which browsers
+/// decode before following the URL.
+///
+/// ```
+/// use mrmx_markup::url::decode;
+///
+/// assert_eq!(decode("java&#x09;script&colon;"), "java\tscript:");
+/// ```
+pub fn decode(value: &str) -> String {
+    let mut decoded = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = rest.find(';').filter(|&end| end <= 10);
+        let reference = end.and_then(|end| {
+            let c = match &rest[1..end] {
+                "amp" => '&',
+                "lt" => '<',
+                "gt" => '>',
+                "quot" => '"',
+                "apos" => '\'',
+                "colon" => ':',
+                "Tab" => '\t',
+                "NewLine" => '\n',
+                name => {
+                    let number = name.strip_prefix('#')?;
+                    let code = match number.strip_prefix(['x', 'X']) {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                        None => number.parse().ok()?,
+                    };
+                    char::from_u32(code)?
+                }
+            };
+            Some((c, end + 1))
+        });
+        match reference {
+            Some((c, len)) => {
+                decoded.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+fn is_scheme(scheme: &str) -> bool {
+    let mut chars = scheme.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+}
+
+/// The scheme of `url`, if it is absolute.
+fn scheme(url: &str) -> Option<&str> {
+    let (scheme, _) = url.split_once(':')?;
+    is_scheme(scheme).then_some(scheme)
+}
+
+/// The host of a URL after its scheme, like `//user@host:443/path`.
+fn host(rest: &str) -> Option<&str> {
+    let authority = rest.strip_prefix("//")?;
+    let authority = &authority[..authority.find(['/', '?', '#']).unwrap_or(authority.len())];
+    let host = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    if let Some(ipv6) = host.strip_prefix('[') {
+        return ipv6.split_once(']').map(|(ipv6, _)| ipv6);
+    }
+    Some(host.split_once(':').map_or(host, |(host, _)| host))
+}
+
+fn is_private_host(host: &str) -> bool {
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+    if let Ok(ip) = host.parse::<Ipv4Addr>() {
+        return ip.is_private() || ip.is_loopback() || ip.is_link_local() || ip.is_unspecified();
+    }
+    if let Ok(ip) = host.parse::<Ipv6Addr>() {
+        let first = ip.segments()[0];
+        return ip.is_loopback()
+            || ip.is_unspecified()
+            || first & 0xfe00 == 0xfc00
+            || first & 0xffc0 == 0xfe80;
+    }
+    host == "localhost"
+        || [".localhost", ".local", ".internal"]
+            .iter()
+            .any(|suffix| host.ends_with(suffix))
+}
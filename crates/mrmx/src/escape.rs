@@ -0,0 +1,137 @@
+//! HTML escaping of the values interpolated in [`view!`](crate::view).
+//!
+//! mrml prints text and attribute values as they are, so `view!` escapes
+//! strings and numbers in blocks, in attribute values and in `<mj-title>` and
+//! `<mj-preview>`:
+//!
+//! ```
+//! # use mrmx::{view, Render};
+//! let name = "Tom & Jerry <script>";
+//! let text = view! { <mj-text>"Hi "{name}</mj-text> };
+//! assert_eq!(text.to_mjml(), "<mj-text>Hi Tom &amp; Jerry &lt;script&gt;</mj-text>");
+//! ```
+//!
+//! Trusted markup is wrapped in [`raw`] to insert it as it is:
+//!
+//! ```
+//! # use mrmx::{raw, view, Render};
+//! let signature = "<b>The team</b>";
+//! let text = view! { <mj-text>{raw(signature)}</mj-text> };
+//! assert_eq!(text.to_mjml(), "<mj-text><b>The team</b></mj-text>");
+//! ```
+//!
+//! Literals written in the markup are trusted, and never escaped. Neither are
+//! [`Text`] nodes built by hand, nor the contents of `<mj-style>`, which is
+//! CSS rather than HTML. For the same reason, the attributes that mrml writes
+//! into CSS are left as they are: the `name` and `href` of `<mj-font>`, the
+//! `width` of `<mj-breakpoint>` and the `inline` of `<mj-style>`.
+
+use std::fmt::Display;
+
+use mrml::text::Text;
+
+use crate::IntoChildren;
+
+/// Escapes the characters of `text` that are special in HTML text and
+/// attribute values.
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Trusted markup, inserted by [`view!`](crate::view) without escaping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Raw<T>(pub T);
+
+/// Marks `markup` as trusted, so that [`view!`](crate::view) inserts it as it
+/// is.
+pub fn raw<T: Display>(markup: T) -> Raw<T> {
+    Raw(markup)
+}
+
+/// Conversion of a value interpolated in text into HTML, escaping it unless it
+/// is [`Raw`].
+pub trait HtmlText {
+    fn html_text(&self) -> String;
+}
+
+impl<T: Display + ?Sized> HtmlText for T {
+    fn html_text(&self) -> String {
+        escape_html(&self.to_string())
+    }
+}
+
+impl<T: Display> HtmlText for Raw<T> {
+    fn html_text(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+/// Escapes the values of a collection of attributes spread with `{..attrs}`.
+pub fn escape_attributes<I, K, V>(attributes: I) -> impl Iterator<Item = (K, String)>
+where
+    I: IntoIterator<Item = (K, V)>,
+    V: HtmlText,
+{
+    attributes
+        .into_iter()
+        .map(|(key, value)| (key, value.html_text()))
+}
+
+#[doc(hidden)]
+pub struct TextChild;
+
+macro_rules! text_children {
+    ($($ty:ty),*) => {
+        $(
+            impl<C: From<Text>> IntoChildren<C, TextChild> for $ty {
+                fn into_children(self, children: &mut Vec<C>) {
+                    children.push(Text::from(self.html_text()).into());
+                }
+            }
+        )*
+    };
+}
+
+text_children!(&str, String, &String, std::borrow::Cow<'_, str>);
+text_children!(char, i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64);
+
+impl<C: From<Text>, T: Display> IntoChildren<C, TextChild> for Raw<T> {
+    fn into_children(self, children: &mut Vec<C>) {
+        children.push(Text::from(self.html_text()).into());
+    }
+}
+
+/// A child of an element that only holds [`Text`], like
+/// `<mj-accordion-title>`.
+///
+/// `Text` converts from any string, so strings would be pushed into a
+/// `Vec<Text>` as they are. This type only converts from `Text`, so that
+/// strings go through [`HtmlText`] instead.
+#[doc(hidden)]
+pub struct TextNode(Text);
+
+impl From<Text> for TextNode {
+    fn from(text: Text) -> Self {
+        TextNode(text)
+    }
+}
+
+/// Appends the children of a block in an element that only holds [`Text`],
+/// escaping strings.
+#[doc(hidden)]
+pub fn into_text_children<M>(block: impl IntoChildren<TextNode, M>, children: &mut Vec<Text>) {
+    let mut nodes = Vec::new();
+    block.into_children(&mut nodes);
+    children.extend(nodes.into_iter().map(|TextNode(text)| text));
+}
@@ -0,0 +1,355 @@
+//! `<mj-html-attributes>`, which sets attributes on the rendered HTML of the
+//! elements matching a CSS selector.
+//!
+//! mrml has no such element, so [`view!`](crate::view) builds the types in
+//! this module instead. In `<mj-head>`, mrml has no room for them either, so
+//! they are carried by an `<mj-attributes>` that only holds them, which mrml
+//! renders without any effect. [`Render`](crate::Render) prints it back as
+//! `<mj-html-attributes>`, and with the `render` feature, applies it to the
+//! HTML:
+//!
+//! ```
+//! # use mrmx::view;
+//! use mrmx::html_attributes::MjHtmlAttributes;
+//! use mrmx::Render;
+//!
+//! let email = view! {
+//!     <mjml>
+//!         <mj-head>
+//!             <mj-html-attributes>
+//!                 <mj-selector path=".cta a">
+//!                     <mj-html-attribute name="data-id">"42"</mj-html-attribute>
+//!                 </mj-selector>
+//!             </mj-html-attributes>
+//!         </mj-head>
+//!         <mj-body></mj-body>
+//!     </mjml>
+//! };
+//! let head = email.children.head.as_ref().unwrap();
+//! assert_eq!(MjHtmlAttributes::in_head(head).len(), 1);
+//! assert!(email.to_mjml().contains("<mj-html-attributes>"));
+//! ```
+//!
+//! Comments in `<mj-html-attributes>` are dropped in the head, since
+//! `<mj-attributes>` has no room for them. When rendering with mrml directly,
+//! call [`MjHtmlAttributes::apply`] on the output for each of
+//! [`MjHtmlAttributes::in_head`].
+//!
+//! Selectors are parsed by [lol_html](https://docs.rs/lol_html/), which supports
+//! type, class, id and attribute selectors, descendant and `>` combinators,
+//! and a few pseudo-classes like `:first-child` and `:not()`. Other selectors,
+//! like `a + b` or `a:hover`, are an error.
+
+use mrml::comment::Comment;
+use mrml::mj_attributes::{MjAttributes, MjAttributesChild};
+use mrml::mj_attributes_element::MjAttributesElement;
+use mrml::mj_head::{MjHead, MjHeadChild};
+use mrml::mj_include::head::MjIncludeHeadChild;
+use mrml::mj_raw::MjRawChild;
+use mrml::node::Node;
+
+use crate::{WithAttribute, WithChildren};
+
+/// The `<mj-html-attributes>` element.
+#[derive(Clone, Debug, Default)]
+pub struct MjHtmlAttributes {
+    pub children: Vec<MjHtmlAttributesChild>,
+}
+
+#[derive(Clone, Debug)]
+pub enum MjHtmlAttributesChild {
+    Comment(Comment),
+    MjSelector(MjSelector),
+}
+
+impl From<Comment> for MjHtmlAttributesChild {
+    fn from(value: Comment) -> Self {
+        MjHtmlAttributesChild::Comment(value)
+    }
+}
+impl From<MjSelector> for MjHtmlAttributesChild {
+    fn from(value: MjSelector) -> Self {
+        MjHtmlAttributesChild::MjSelector(value)
+    }
+}
+
+/// The `<mj-selector>` element, whose `path` is the CSS selector.
+#[derive(Clone, Debug, Default)]
+pub struct MjSelector {
+    pub path: String,
+    pub children: Vec<MjSelectorChild>,
+}
+
+#[derive(Clone, Debug)]
+pub enum MjSelectorChild {
+    Comment(Comment),
+    MjHtmlAttribute(MjHtmlAttribute),
+}
+
+impl From<Comment> for MjSelectorChild {
+    fn from(value: Comment) -> Self {
+        MjSelectorChild::Comment(value)
+    }
+}
+impl From<MjHtmlAttribute> for MjSelectorChild {
+    fn from(value: MjHtmlAttribute) -> Self {
+        MjSelectorChild::MjHtmlAttribute(value)
+    }
+}
+
+/// The `<mj-html-attribute>` element, whose content is the attribute value.
+#[derive(Clone, Debug, Default)]
+pub struct MjHtmlAttribute {
+    pub name: String,
+    pub value: String,
+}
+
+impl From<String> for MjHtmlAttribute {
+    fn from(value: String) -> Self {
+        Self {
+            name: String::new(),
+            value,
+        }
+    }
+}
+
+impl From<&str> for MjHtmlAttribute {
+    fn from(value: &str) -> Self {
+        Self::from(value.to_string())
+    }
+}
+
+impl From<MjHtmlAttributes> for MjHeadChild {
+    fn from(value: MjHtmlAttributes) -> Self {
+        MjHeadChild::MjAttributes(value.to_carrier())
+    }
+}
+
+impl From<MjHtmlAttributes> for MjIncludeHeadChild {
+    fn from(value: MjHtmlAttributes) -> Self {
+        MjIncludeHeadChild::MjAttributes(value.to_carrier())
+    }
+}
+
+impl MjHtmlAttributes {
+    /// The `<mj-attributes>` that carries this element in `<mj-head>`. Its first
+    /// child is named `mj-html-attributes`, and each `<mj-selector>` and each of
+    /// its `<mj-html-attribute>` follow, in order, with their values as
+    /// attributes.
+    fn to_carrier(&self) -> MjAttributes {
+        fn element(name: &str, attributes: &[(&str, &str)]) -> MjAttributesChild {
+            let mut element = MjAttributesElement::new(name.to_string());
+            for (key, value) in attributes {
+                element
+                    .attributes
+                    .insert(key.to_string(), value.to_string());
+            }
+            element.into()
+        }
+
+        let mut children = vec![element("mj-html-attributes", &[])];
+        for selector in self.selectors() {
+            children.push(element("mj-selector", &[("path", &selector.path)]));
+            for attr in selector.attributes() {
+                let attributes = [("name", attr.name.as_str()), ("value", &attr.value)];
+                children.push(element("mj-html-attribute", &attributes));
+            }
+        }
+        MjAttributes::default().with_children(children)
+    }
+
+    /// The `<mj-html-attributes>` that `attributes` carries, if it is one.
+    pub fn from_carrier(attributes: &MjAttributes) -> Option<Self> {
+        let (first, rest) = attributes.children.split_first()?;
+        match first {
+            MjAttributesChild::MjAttributesElement(element)
+                if element.name == "mj-html-attributes" => {}
+            _ => return None,
+        }
+        let mut selectors: Vec<MjSelector> = Vec::new();
+        for child in rest {
+            let MjAttributesChild::MjAttributesElement(element) = child else {
+                return None;
+            };
+            let value = |key: &str| element.attributes.get(key).cloned().unwrap_or_default();
+            match element.name.as_str() {
+                "mj-selector" => selectors.push(MjSelector {
+                    path: value("path"),
+                    children: Vec::new(),
+                }),
+                "mj-html-attribute" => selectors.last_mut()?.children.push(
+                    MjHtmlAttribute {
+                        name: value("name"),
+                        value: value("value"),
+                    }
+                    .into(),
+                ),
+                _ => return None,
+            }
+        }
+        let children = selectors.into_iter().map(Into::into).collect();
+        Some(Self { children })
+    }
+
+    /// The `<mj-html-attributes>` carried in `head`, including those of the
+    /// partials it includes, in order.
+    pub fn in_head(head: &MjHead) -> Vec<Self> {
+        head.children
+            .iter()
+            .flat_map(|child| match child {
+                MjHeadChild::MjAttributes(attributes) => vec![attributes],
+                MjHeadChild::MjInclude(include) => include
+                    .children
+                    .iter()
+                    .filter_map(|child| match child {
+                        MjIncludeHeadChild::MjAttributes(attributes) => Some(attributes),
+                        _ => None,
+                    })
+                    .collect(),
+                _ => Vec::new(),
+            })
+            .filter_map(Self::from_carrier)
+            .collect()
+    }
+
+    fn selectors(&self) -> impl Iterator<Item = &MjSelector> {
+        self.children.iter().filter_map(|child| match child {
+            MjHtmlAttributesChild::MjSelector(selector) => Some(selector),
+            MjHtmlAttributesChild::Comment(_) => None,
+        })
+    }
+
+    /// The markup of this element, since mrml cannot print it.
+    pub(crate) fn to_node(&self) -> Node<MjRawChild> {
+        let children = self
+            .children
+            .iter()
+            .map(|child| match child {
+                MjHtmlAttributesChild::Comment(comment) => comment.clone().into(),
+                MjHtmlAttributesChild::MjSelector(selector) => selector.to_node().into(),
+            })
+            .collect();
+        Node::from("mj-html-attributes").with_children(children)
+    }
+
+    /// Sets the attributes on the elements of `html` that match each selector.
+    ///
+    /// ```
+    /// use mrmx::html_attributes::{MjHtmlAttribute, MjHtmlAttributes, MjSelector};
+    /// use mrmx::{WithAttribute, WithChildren};
+    ///
+    /// let attribute = MjHtmlAttribute::from("42").with_attribute("name".into(), "data-id".into());
+    /// let selector = MjSelector::default()
+    ///     .with_attribute("path".into(), ".cta a".into())
+    ///     .with_children(vec![attribute.into()]);
+    /// let attributes = MjHtmlAttributes::default().with_children(vec![selector.into()]);
+    /// assert_eq!(
+    ///     attributes.apply(r#"<div class="cta"><a href="/">Buy</a></div>"#)?,
+    ///     r#"<div class="cta"><a href="/" data-id="42">Buy</a></div>"#,
+    /// );
+    /// # Ok::<(), mrmx::html_attributes::HtmlAttributesError>(())
+    /// ```
+    #[cfg(feature = "render")]
+    pub fn apply(&self, html: &str) -> Result<String, HtmlAttributesError> {
+        use std::borrow::Cow;
+
+        use lol_html::html_content::Element;
+        use lol_html::{ElementContentHandlers, RewriteStrSettings, Selector};
+
+        let mut handlers = Vec::new();
+        for selector in self.selectors() {
+            let path = selector.path.parse::<Selector>().map_err(|error| {
+                HtmlAttributesError::Selector {
+                    path: selector.path.clone(),
+                    error,
+                }
+            })?;
+            let attributes = selector.attributes().collect::<Vec<_>>();
+            let set = ElementContentHandlers::default().element(move |element: &mut Element| {
+                for attr in &attributes {
+                    element.set_attribute(&attr.name, &attr.value)?;
+                }
+                Ok(())
+            });
+            handlers.push((Cow::Owned(path), set));
+        }
+        if handlers.is_empty() {
+            return Ok(html.to_string());
+        }
+        let settings = RewriteStrSettings {
+            element_content_handlers: handlers,
+            ..RewriteStrSettings::new()
+        };
+        lol_html::rewrite_str(html, settings).map_err(HtmlAttributesError::Rewrite)
+    }
+}
+
+impl MjSelector {
+    fn attributes(&self) -> impl Iterator<Item = &MjHtmlAttribute> {
+        self.children.iter().filter_map(|child| match child {
+            MjSelectorChild::MjHtmlAttribute(attr) => Some(attr),
+            MjSelectorChild::Comment(_) => None,
+        })
+    }
+
+    pub(crate) fn to_node(&self) -> Node<MjRawChild> {
+        let children = self
+            .children
+            .iter()
+            .map(|child| match child {
+                MjSelectorChild::Comment(comment) => comment.clone().into(),
+                MjSelectorChild::MjHtmlAttribute(attr) => attr.to_node().into(),
+            })
+            .collect();
+        Node::from("mj-selector")
+            .with_attribute("path".to_string(), self.path.clone())
+            .with_children(children)
+    }
+}
+
+impl MjHtmlAttribute {
+    pub(crate) fn to_node(&self) -> Node<MjRawChild> {
+        Node::from("mj-html-attribute")
+            .with_attribute("name".to_string(), self.name.clone())
+            .with_children(vec![mrml::text::Text::from(self.value.clone()).into()])
+    }
+}
+
+/// Why [`MjHtmlAttributes::apply`] failed.
+#[cfg(feature = "render")]
+#[derive(Debug)]
+pub enum HtmlAttributesError {
+    /// The `path` of an `<mj-selector>` is not a selector that can be applied,
+    /// like `a + b`.
+    Selector {
+        path: String,
+        error: lol_html::errors::SelectorError,
+    },
+    /// The HTML could not be rewritten, like when an attribute name is
+    /// invalid.
+    Rewrite(lol_html::errors::RewritingError),
+}
+
+#[cfg(feature = "render")]
+impl std::fmt::Display for HtmlAttributesError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HtmlAttributesError::Selector { path, error } => {
+                write!(f, "invalid selector `{path}`: {error}")
+            }
+            HtmlAttributesError::Rewrite(error) => {
+                write!(f, "cannot set the HTML attributes: {error}")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "render")]
+impl std::error::Error for HtmlAttributesError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            HtmlAttributesError::Selector { error, .. } => Some(error),
+            HtmlAttributesError::Rewrite(error) => Some(error),
+        }
+    }
+}
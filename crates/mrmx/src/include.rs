@@ -0,0 +1,66 @@
+//! Loading of `<mj-include>` partials with mrml's parser.
+//!
+//! [`view!`](crate::view) expands `<mj-include path="..." />` at compile
+//! time, relative to the calling crate's manifest, into the tree of the file.
+//! To choose the file at runtime instead, call [`include_body`] or
+//! [`include_head`] from a block, with an include loader from
+//! [`mrml::prelude::parser`]:
+//!
+//! ```
+//! # use mrmx::view;
+//! use mrml::prelude::parser::memory_loader::MemoryIncludeLoader;
+//! use mrml::prelude::parser::ParserOptions;
+//!
+//! let options = ParserOptions {
+//!     include_loader: Box::new(MemoryIncludeLoader::from(vec![(
+//!         "footer.mjml",
+//!         "<mj-section><mj-column><mj-text>Bye</mj-text></mj-column></mj-section>",
+//!     )])),
+//! };
+//! let footer = mrmx::include::include_body("footer.mjml", &options)?;
+//! view! {
+//!     <mj-body>
+//!         {footer}
+//!     </mj-body>
+//! };
+//! # Ok::<(), mrml::prelude::parser::Error>(())
+//! ```
+
+use mrml::mj_body::MjBodyChild;
+use mrml::mj_head::MjHeadChild;
+use mrml::mj_include::body::MjIncludeBody;
+use mrml::mj_include::head::MjIncludeHead;
+use mrml::prelude::parser::{Error, ParserOptions};
+
+/// Loads the body partial at `path` through the include loader of `options`.
+pub fn include_body(path: &str, options: &ParserOptions) -> Result<MjIncludeBody, Error> {
+    let document = format!("<mjml><mj-body>{}</mj-body></mjml>", include_tag(path));
+    let mjml = mrml::parse_with_options(document, options)?.element;
+    match mjml
+        .children
+        .body
+        .and_then(|body| body.children.into_iter().next())
+    {
+        Some(MjBodyChild::MjInclude(include)) => Ok(include),
+        _ => unreachable!("the document consists of a single mj-include"),
+    }
+}
+
+/// Loads the head partial at `path` through the include loader of `options`.
+pub fn include_head(path: &str, options: &ParserOptions) -> Result<MjIncludeHead, Error> {
+    let document = format!("<mjml><mj-head>{}</mj-head></mjml>", include_tag(path));
+    let mjml = mrml::parse_with_options(document, options)?.element;
+    match mjml
+        .children
+        .head
+        .and_then(|head| head.children.into_iter().next())
+    {
+        Some(MjHeadChild::MjInclude(include)) => Ok(include),
+        _ => unreachable!("the document consists of a single mj-include"),
+    }
+}
+
+fn include_tag(path: &str) -> String {
+    let path = path.replace('&', "&amp;").replace('"', "&quot;");
+    format!("<mj-include path=\"{path}\" />")
+}
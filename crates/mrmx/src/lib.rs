@@ -1,5 +1,10 @@
 //! The mrmx crate provides a JSX-like syntax for generating Mjml.
 //!
+//! The generated code only needs `mrmx` itself, even when it is renamed in
+//! `Cargo.toml`: traits are called by their full path, and the
+//! [`mrml`](https://docs.rs/mrml/) types are reached through the re-exported
+//! [`mrml`] module.
+//!
 //! It allows generating subsections of a document:
 //!
 //! ```
@@ -30,7 +35,7 @@
 //! view! {
 //!     <mjml>
 //!         <mj-head>
-//!             { title.into() }
+//!             { title }
 //!         </mj-head>
 //!         <mj-body>
 //!             <!-- "Single quotes must be contained in strings" -->
@@ -59,9 +64,350 @@
 //!     </mjml>
 //! };
 //! ```
+//!
+//! `<mj-title>`, `<mj-preview>` and `<mj-style>` hold plain text, which can mix
+//! literals with expressions that implement [`Display`](std::fmt::Display):
+//!
+//! ```
+//! # use mrmx::view;
+//! let order = 42;
+//! view! { <mj-title>"Order #"{order}" shipped"</mj-title> };
+//! ```
+//!
+//! Attribute values can be any Rust expression, wrapped in braces:
+//!
+//! ```
+//! # use mrmx::view;
+//! let url = "https://example.com";
+//! let padding = 10;
+//! view! {
+//!     <mj-button href={url} padding={format!("{padding}px")}>"Visit"</mj-button>
+//! };
+//! ```
+//!
+//! Expressions are converted with [`IntoAttributeValue`] when they implement
+//! it, and through [`Display`](std::fmt::Display) otherwise. An attribute whose
+//! value is `None` or `false` is left out entirely:
+//!
+//! ```
+//! # use mrmx::view;
+//! let alt: Option<&str> = None;
+//! view! {
+//!     <mj-image src="https://example.com/logo.png" alt={alt} />
+//! };
+//! ```
+//!
+//! A collection of attributes, such as a map loaded from configuration, can be
+//! spread onto an element. Attributes are applied in order, so later ones take
+//! precedence:
+//!
+//! ```
+//! # use mrmx::view;
+//! let brand = [("background-color", "#2f4f4f"), ("color", "#000000")];
+//! view! {
+//!     <mj-button {..brand} color="#ffffff">"Visit"</mj-button>
+//! };
+//! ```
+//!
+//! Strings and numbers interpolated in text, attribute values, `<mj-title>` and
+//! `<mj-preview>` are HTML-escaped, while literals are used as written. Trusted
+//! markup is inserted as it is by wrapping it in [`raw`]:
+//!
+//! ```
+//! # use mrmx::view;
+//! use mrmx::raw;
+//! let name = "Tom & Jerry";
+//! let signature = "<b>The team</b>";
+//! view! {
+//!     <mj-text>"Hi "{name}", from "{raw(signature)}</mj-text>
+//! };
+//! ```
+//!
+//! Attribute names on `mj-*` elements are checked against the MJML
+//! documentation at compile time, as are literal lengths and colors, so a typo
+//! like `colour="red"` is an error that suggests `color`. Attributes starting
+//! with `data-` are never checked:
+//!
+//! ```
+//! # use mrmx::view;
+//! view! {
+//!     <mj-button padding="10px 25px" background-color="#2f4f4f" data-track="cta">
+//!         "Visit"
+//!     </mj-button>
+//! };
+//! ```
+//!
+//! URLs in `href`, `src`, `background-url` and similar attributes must be
+//! absolute `https://` URLs to public hosts. Literal URLs are checked at compile
+//! time, and rendering with [`Render`] checks the rest. Other URLs can be
+//! allowed at compile time with `#![allowed_urls("mailto:")]` at the start of
+//! the view, while rendering also accepts `mailto:`, `tel:` and relative URLs,
+//! as described in [`url_policy`].
+//!
+//! The root `<mjml>` element takes `lang`, `dir` and `owa`, for localized and
+//! right-to-left emails:
+//!
+//! ```
+//! # use mrmx::view;
+//! view! {
+//!     <mjml lang="ar" dir="rtl">
+//!         <mj-body></mj-body>
+//!     </mjml>
+//! };
+//! ```
+//!
+//! `<mjml>`, `<mj-font>`, `<mj-breakpoint>` and `<mj-style>` only have fields
+//! for their own attributes, so even `data-` attributes are an error on them.
+//! When building these elements by hand, [`TryWithAttribute`] reports
+//! unsupported attributes instead of dropping them.
+//!
+//! `<mj-html-attributes>`, which mrml does not model, is built from the types
+//! in [`html_attributes`] and carried in the head by an `<mj-attributes>`, to
+//! be applied when rendering with [`Render`].
+//!
+//! Likewise, a tag starting with `mj-` that is not an MJML element, like
+//! `<mj-colum>`, is an error rather than being rendered as plain HTML. Custom
+//! tags can be registered at the start of the view:
+//!
+//! ```
+//! # use mrmx::view;
+//! view! {
+//!     #![custom_tags("mj-chart")]
+//!     <mj-column>
+//!         <mj-chart data-kind="bar"></mj-chart>
+//!     </mj-column>
+//! };
+//! ```
+//!
+//! Or for the whole crate, in the `MRMX_CUSTOM_TAGS` environment variable, for
+//! example from `.cargo/config.toml`:
+//!
+//! ```toml
+//! [env]
+//! MRMX_CUSTOM_TAGS = "mj-chart, mj-qr-code"
+//! ```
+//!
+//! Children can be repeated with a `for` loop, whose body is markup, or spread
+//! from anything iterable whose items convert into the parent's children:
+//!
+//! ```
+//! # use mrmx::view;
+//! # use mrml::mj_body::MjBodyChild;
+//! let footer: Vec<MjBodyChild> = vec![view! { <mj-divider /> }.into()];
+//! view! {
+//!     <mj-column>
+//!         {for _ in 0..3 {
+//!             <mj-spacer />
+//!         }}
+//!         {..footer}
+//!     </mj-column>
+//! };
+//! ```
+//!
+//! Markup can be chosen with `if`, `else if`, `else` and `match`. A branch may
+//! contain any number of children, including none:
+//!
+//! ```
+//! # use mrmx::view;
+//! let shipped = true;
+//! let items = 2;
+//! view! {
+//!     <mj-column>
+//!         {if shipped {
+//!             <mj-text>"On its way"</mj-text>
+//!         } else {
+//!             <mj-text>"Preparing"</mj-text>
+//!         }}
+//!         {match items {
+//!             0 => {}
+//!             1 => <mj-text>"One item"</mj-text>,
+//!             _ => { <mj-text>"Several items"</mj-text> <mj-divider /> }
+//!         }}
+//!     </mj-column>
+//! };
+//! ```
+//!
+//! Partials can be included with `<mj-include>`. The path is resolved relative
+//! to the crate's `Cargo.toml` at compile time, or to the including file in a
+//! partial. The partial is checked like the rest of the view, and read with
+//! mrml's parser as it would be at runtime, then built into the view, so
+//! changing the file rebuilds the view. `type="html"` includes a file as raw
+//! HTML, and `type="css"` a stylesheet in `<mj-head>`. See [`include`] to load
+//! partials at runtime instead.
+//!
+//! ```
+//! # use mrmx::view;
+//! view! {
+//!     <mj-body>
+//!         <mj-include path="tests/partials/footer.mjml" />
+//!     </mj-body>
+//! };
+//! ```
+//!
+//! Whole templates can be kept in `.mjml` files and embedded with
+//! [`mjml_file!`], which builds the same tree as `view!` would from the file's
+//! markup. Text and attribute values can use `{expr}` placeholders, filled
+//! from the variables in scope. The template goes through the same checks as
+//! `view!`, with errors reported at their line and column in the file:
+//!
+//! ```
+//! let name = "Ada";
+//! let accent = "#ff6600";
+//! let id = 7;
+//! mrmx::mjml_file!("tests/templates/welcome.mjml");
+//! ```
+//!
+//! Everything `view!` builds implements [`Render`], to print it as MJML, or
+//! with the `render` feature, to render it to HTML:
+//!
+//! ```
+//! # use mrmx::{view, Render};
+//! let email = view! {
+//!     <mjml>
+//!         <mj-body>
+//!             <mj-section>
+//!                 <mj-column>
+//!                     <mj-text>"Hello"</mj-text>
+//!                 </mj-column>
+//!             </mj-section>
+//!         </mj-body>
+//!     </mjml>
+//! };
+//! let mjml = email.to_mjml();
+//! # #[cfg(feature = "render")]
+//! let html = email.to_html(&Default::default())?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! Tags with a PascalCase name, like `<OrderSummary order={order} />`, are
+//! components: Rust functions that build a piece of markup from their
+//! [`Props`]. The [`component`] attribute generates the props for a function
+//! from its arguments:
+//!
+//! ```
+//! # use mrmx::{component, view};
+//! # use mrml::mj_body::MjBodyChild;
+//! #[component]
+//! fn Footer(company: &str, #[prop(default)] show_social: bool) -> MjBodyChild {
+//!     if show_social {
+//!         view! { <mj-social><mj-social-element name="github" /></mj-social> }.into()
+//!     } else {
+//!         view! { <mj-text>{company}</mj-text> }.into()
+//!     }
+//! }
+//!
+//! view! {
+//!     <mj-column>
+//!         <Footer company="ACME" />
+//!     </mj-column>
+//! };
+//! ```
+
+// Lets the code generated by `view!` name this crate `::mrmx` from within.
+extern crate self as mrmx;
 
 #[cfg(feature = "macros")]
-pub use mrmx_macros::view;
+pub use mrmx_macros::{component, mjml_file, view};
+
+/// The version of [`mrml`](https://docs.rs/mrml/) that `view!` builds, so that
+/// its types can be named without depending on it directly.
+pub use mrml;
+
+pub mod escape;
+pub mod html_attributes;
+#[cfg(feature = "include")]
+pub mod include;
+mod render;
+pub mod url_policy;
+
+pub use escape::raw;
+pub use render::Render;
+#[cfg(feature = "render")]
+pub use render::RenderError;
+
+/// The properties of a component that can be used as a tag in [`view!`].
+///
+/// A component is a function taking its props, whose name is PascalCase:
+/// `<Cta href="/buy" />` expands to roughly
+/// `Cta(CtaProps::builder().href("/buy").build())`. Each attribute calls the
+/// builder method of the same name, with dashes replaced by underscores, and
+/// any children are passed as a `Vec` to a `children` method. The value the
+/// component returns is converted into the parent's child type like any other
+/// node.
+///
+/// ```
+/// # use mrmx::{view, Props};
+/// # use mrml::mj_button::MjButton;
+/// struct CtaProps {
+///     href: String,
+/// }
+///
+/// #[derive(Default)]
+/// struct CtaPropsBuilder {
+///     href: Option<String>,
+/// }
+///
+/// impl CtaPropsBuilder {
+///     fn href(mut self, href: impl Into<String>) -> Self {
+///         self.href = Some(href.into());
+///         self
+///     }
+///
+///     fn build(self) -> CtaProps {
+///         CtaProps {
+///             href: self.href.expect("href is required"),
+///         }
+///     }
+/// }
+///
+/// impl Props for CtaProps {
+///     type Builder = CtaPropsBuilder;
+///
+///     fn builder() -> Self::Builder {
+///         CtaPropsBuilder::default()
+///     }
+/// }
+///
+/// #[allow(non_snake_case)]
+/// fn Cta(props: CtaProps) -> MjButton {
+///     view! { <mj-button href={props.href}>"Buy now"</mj-button> }
+/// }
+///
+/// view! {
+///     <mj-column>
+///         <Cta href="/buy" />
+///     </mj-column>
+/// };
+/// ```
+pub trait Props {
+    type Builder;
+
+    fn builder() -> Self::Builder;
+}
+
+/// Builder for components that take no props, as `()`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct EmptyPropsBuilder;
+
+impl EmptyPropsBuilder {
+    pub fn build(self) {}
+}
+
+impl Props for () {
+    type Builder = EmptyPropsBuilder;
+
+    fn builder() -> Self::Builder {
+        EmptyPropsBuilder
+    }
+}
+
+#[doc(hidden)]
+pub fn component_props_builder<P, O>(_component: &impl Fn(P) -> O) -> P::Builder
+where
+    P: Props,
+{
+    P::builder()
+}
 
 pub trait WithChildren {
     type Child;
@@ -78,6 +424,95 @@ impl<T> WithChildren for mrml::node::Node<T> {
     }
 }
 
+/// Conversion of a block used as a child in [`view!`] into any number of
+/// children of type `C`.
+///
+/// It is implemented for anything that converts into `C`, for `Option`s,
+/// `Vec`s and other iterables of those, and for
+/// [`Fragment`](mrml::fragment::Fragment)s, so a block can yield zero, one or
+/// many children without calling `.into()`:
+///
+/// ```
+/// # use mrmx::view;
+/// let subtitle = Some(view! { <mj-text>"Order #42"</mj-text> });
+/// let lines = ["Socks", "Shoes"].map(|item| {
+///     view! { <mj-text>{item}</mj-text> }
+/// });
+/// view! {
+///     <mj-column>
+///         <mj-text>"Thanks for your order"</mj-text>
+///         {subtitle}
+///         {lines}
+///     </mj-column>
+/// };
+/// ```
+///
+/// A block that ends in an explicit `.into()` or `Into::into(..)`, on every
+/// branch of an `if` or `match`, is pushed as a single child instead, since
+/// the target of the conversion could not be inferred through this trait.
+/// `view!` only spots the call in the syntax of the block, so a conversion
+/// behind a variable needs a type annotation:
+///
+/// ```
+/// # use mrmx::view;
+/// use mrmx::mrml::mj_body::MjBodyChild;
+/// use mrmx::mrml::text::Text;
+///
+/// let note = Text::from("Ships in 2 days");
+/// view! {
+///     <mj-text>
+///         {note.clone().into()}
+///         {if true { note.clone().into() } else { Into::into(Text::from("")) }}
+///         {let child: MjBodyChild = note.into(); child}
+///     </mj-text>
+/// };
+/// ```
+///
+/// `Marker` only tells the implementations apart, and is always inferred.
+pub trait IntoChildren<C, Marker> {
+    /// Appends the children to `children`.
+    fn into_children(self, children: &mut Vec<C>);
+}
+
+#[doc(hidden)]
+pub struct SingleChild;
+
+#[doc(hidden)]
+pub struct IterChildren<M>(std::marker::PhantomData<M>);
+
+#[doc(hidden)]
+pub struct FragmentChildren;
+
+impl<C, T> IntoChildren<C, SingleChild> for T
+where
+    T: Into<C>,
+{
+    fn into_children(self, children: &mut Vec<C>) {
+        children.push(self.into());
+    }
+}
+
+impl<C, I, M> IntoChildren<C, IterChildren<M>> for I
+where
+    I: IntoIterator,
+    I::Item: IntoChildren<C, M>,
+{
+    fn into_children(self, children: &mut Vec<C>) {
+        for child in self {
+            child.into_children(children);
+        }
+    }
+}
+
+impl<C, T> IntoChildren<C, FragmentChildren> for mrml::fragment::Fragment<T>
+where
+    T: Into<C>,
+{
+    fn into_children(self, children: &mut Vec<C>) {
+        children.extend(self.children.into_iter().map(Into::into));
+    }
+}
+
 pub enum MjmlChild {
     Head(mrml::mj_head::MjHead),
     Body(mrml::mj_body::MjBody),
@@ -94,14 +529,26 @@ impl From<mrml::mj_body::MjBody> for MjmlChild {
     }
 }
 
+/// A second `<mj-head>` or `<mj-body>` is merged into the first, so documents
+/// can be composed from layout pieces. Its children are appended, and its
+/// attributes override those of the first.
 impl WithChildren for mrml::mjml::Mjml {
     type Child = MjmlChild;
 
     fn with_children(mut self, children: Vec<MjmlChild>) -> Self {
         for ch in children {
             match ch {
-                MjmlChild::Head(head) => self.children.head = Some(head),
-                MjmlChild::Body(body) => self.children.body = Some(body),
+                MjmlChild::Head(head) => match &mut self.children.head {
+                    Some(first) => first.children.extend(head.children),
+                    None => self.children.head = Some(head),
+                },
+                MjmlChild::Body(body) => match &mut self.children.body {
+                    Some(first) => {
+                        first.attributes.extend(body.attributes);
+                        first.children.extend(body.children);
+                    }
+                    None => self.children.body = Some(body),
+                },
             }
         }
         self
@@ -117,19 +564,92 @@ pub enum MjAccordionElementChild {
     MjAccordionTitle(mrml::mj_accordion_title::MjAccordionTitle),
 }
 
+impl From<mrml::comment::Comment> for MjAccordionElementChild {
+    fn from(value: mrml::comment::Comment) -> Self {
+        MjAccordionElementChild::Comment(value)
+    }
+}
+impl From<mrml::mj_accordion_text::MjAccordionText> for MjAccordionElementChild {
+    fn from(value: mrml::mj_accordion_text::MjAccordionText) -> Self {
+        MjAccordionElementChild::MjAccordionText(value)
+    }
+}
+impl From<mrml::mj_accordion_title::MjAccordionTitle> for MjAccordionElementChild {
+    fn from(value: mrml::mj_accordion_title::MjAccordionTitle) -> Self {
+        MjAccordionElementChild::MjAccordionTitle(value)
+    }
+}
+
+/// Like `<mjml>`, a second title or text is merged into the first. [`view!`]
+/// rejects a second one written directly in the markup.
+///
+/// mrml keeps the title and the text in fields, with no room for the comments
+/// around them, so each comment is kept in the title or text next to it: at the
+/// start of the one written after it, or else at the end of the one written
+/// before it. The title only holds text, so comments are kept in it as the
+/// text of the comment. An element with nothing but comments has no room for
+/// them, and they are dropped.
 impl WithChildren for mrml::mj_accordion_element::MjAccordionElement {
     type Child = MjAccordionElementChild;
 
     fn with_children(mut self, children: Vec<MjAccordionElementChild>) -> Self {
+        use mrml::prelude::print::Printable;
+
+        fn comment_text(comment: mrml::comment::Comment) -> mrml::text::Text {
+            let comment = comment
+                .print_dense()
+                .expect("printing to a string cannot fail");
+            mrml::text::Text::from(comment)
+        }
+
+        let mut comments = Vec::new();
+        let mut title_last = None;
         for ch in children {
             match ch {
-                MjAccordionElementChild::Comment(_) => {}
-                MjAccordionElementChild::MjAccordionText(text) => self.children.text = Some(text),
-                MjAccordionElementChild::MjAccordionTitle(title) => {
-                    self.children.title = Some(title)
+                MjAccordionElementChild::Comment(comment) => comments.push(comment),
+                MjAccordionElementChild::MjAccordionText(mut text) => {
+                    let before = comments.drain(..).map(Into::into);
+                    text.children.splice(0..0, before);
+                    match &mut self.children.text {
+                        Some(first) => {
+                            first.attributes.extend(text.attributes);
+                            first.children.extend(text.children);
+                        }
+                        None => self.children.text = Some(text),
+                    }
+                    title_last = Some(false);
+                }
+                MjAccordionElementChild::MjAccordionTitle(mut title) => {
+                    let before = comments.drain(..).map(comment_text);
+                    title.children.splice(0..0, before);
+                    match &mut self.children.title {
+                        Some(first) => {
+                            first.attributes.extend(title.attributes);
+                            first.children.extend(title.children);
+                        }
+                        None => self.children.title = Some(title),
+                    }
+                    title_last = Some(true);
                 }
             }
         }
+        match (title_last, &mut self.children) {
+            (
+                Some(true),
+                mrml::mj_accordion_element::MjAccordionElementChildren {
+                    title: Some(title), ..
+                },
+            ) => title
+                .children
+                .extend(comments.into_iter().map(comment_text)),
+            (
+                Some(false),
+                mrml::mj_accordion_element::MjAccordionElementChildren {
+                    text: Some(text), ..
+                },
+            ) => text.children.extend(comments.into_iter().map(Into::into)),
+            _ => {}
+        }
         self
     }
 }
@@ -195,24 +715,300 @@ with_children!(
     mrml::mj_include::head::MjIncludeHeadChild
 );
 
+with_children!(
+    html_attributes::MjHtmlAttributes,
+    html_attributes::MjHtmlAttributesChild
+);
+with_children!(
+    html_attributes::MjSelector,
+    html_attributes::MjSelectorChild
+);
+
+/// Implements [`WithChildren`] for an element whose content is a string, by
+/// appending each child.
+macro_rules! with_text_children {
+    ($el:path) => {
+        impl WithChildren for $el {
+            type Child = String;
+
+            fn with_children(mut self, children: Vec<String>) -> Self {
+                self.children.extend(children);
+                self
+            }
+        }
+    };
+}
+
+with_text_children!(mrml::mj_preview::MjPreview);
+with_text_children!(mrml::mj_style::MjStyle);
+with_text_children!(mrml::mj_title::MjTitle);
+
+impl WithChildren for html_attributes::MjHtmlAttribute {
+    type Child = String;
+
+    fn with_children(mut self, children: Vec<String>) -> Self {
+        self.value.extend(children);
+        self
+    }
+}
+
+// The remaining elements have no children:
 // mrml::mj_attributes_all::MjAttributesAll
 // mrml::mj_attributes_class::MjAttributesClass
 // mrml::mj_attributes_element::MjAttributesElement
-
+// mrml::mj_breakpoint::MjBreakpoint
 // mrml::mj_carousel_image::MjCarouselImage
-// mrml::mj_image::MjImage
 // mrml::mj_divider::MjDivider
-// mrml::mj_spacer::MjSpacer
-
-// mrml::mj_breakpoint::MjBreakpoint
 // mrml::mj_font::MjFont
-// mrml::mj_style::MjStyle
-
-// mrml::mj_title::MjTitle
-// mrml::mj_preview::MjPreview
+// mrml::mj_image::MjImage
+// mrml::mj_spacer::MjSpacer
 
 pub trait WithAttribute {
     fn with_attribute(self, key: String, value: String) -> Self;
+
+    /// Sets the attribute `key` to `value`, or leaves the element untouched if
+    /// `value` is `None`.
+    fn with_optional_attribute(self, key: String, value: Option<String>) -> Self
+    where
+        Self: Sized,
+    {
+        match value {
+            Some(value) => self.with_attribute(key, value),
+            None => self,
+        }
+    }
+
+    /// Applies every key-value pair in `attributes` through
+    /// [`with_attribute`](WithAttribute::with_attribute), in order.
+    ///
+    /// This is what `{..attrs}` expands to inside [`view!`], after the values
+    /// are passed through [`escape::escape_attributes`].
+    fn with_attributes<I, K, V>(self, attributes: I) -> Self
+    where
+        Self: Sized,
+        I: IntoIterator<Item = (K, V)>,
+        K: ToString,
+        V: ToString,
+    {
+        attributes.into_iter().fold(self, |el, (key, value)| {
+            el.with_attribute(key.to_string(), value.to_string())
+        })
+    }
+}
+
+/// Fallible version of [`WithAttribute`].
+///
+/// Some elements, like `<mj-font>`, store their attributes in typed fields
+/// and cannot hold other keys. Setting one is an error, rather than the
+/// attribute being dropped:
+///
+/// ```
+/// use mrmx::TryWithAttribute;
+/// use mrml::mj_font::MjFont;
+///
+/// let err = MjFont::default()
+///     .try_with_attribute("nam".to_string(), "Roboto".to_string())
+///     .unwrap_err();
+/// assert_eq!(err.to_string(), "<mj-font> does not support the `nam` attribute");
+/// ```
+///
+/// Elements that accept any attribute always succeed. The [`WithAttribute`]
+/// implementations of the typed elements report unsupported attributes by
+/// panicking in debug builds, and drop them in release builds. [`view!`]
+/// rejects them at compile time, so this only happens to keys set at runtime.
+pub trait TryWithAttribute: Sized {
+    fn try_with_attribute(self, key: String, value: String) -> Result<Self, UnsupportedAttribute>;
+}
+
+/// An attribute set on an element that has no field for it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsupportedAttribute {
+    /// The tag of the element, like `mj-font`.
+    pub element: &'static str,
+    pub attribute: String,
+}
+
+impl UnsupportedAttribute {
+    fn new(element: &'static str, attribute: String) -> Self {
+        Self { element, attribute }
+    }
+}
+
+impl std::fmt::Display for UnsupportedAttribute {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "<{}> does not support the `{}` attribute",
+            self.element, self.attribute
+        )
+    }
+}
+
+impl std::error::Error for UnsupportedAttribute {}
+
+/// Reports the attribute `key`, which `<element>` has no field for, from a
+/// [`WithAttribute`] implementation.
+#[track_caller]
+fn unsupported_attribute(element: &'static str, key: String) {
+    if cfg!(debug_assertions) {
+        panic!("{}", UnsupportedAttribute::new(element, key));
+    }
+}
+
+/// Conversion of an expression used as an attribute value in [`view!`].
+///
+/// Converting to `None` omits the attribute entirely, which is the case for
+/// `None` and `false`. `true` produces an attribute with an empty value.
+/// Strings are [escaped](escape), unless wrapped in [`raw`].
+///
+/// `view!` also accepts values that only implement
+/// [`Display`](std::fmt::Display), like `&i32`, `Arc<str>` or a URL type, and
+/// escapes their text. Implementing this trait takes precedence over
+/// `Display`:
+///
+/// ```
+/// # use mrmx::{view, Render};
+/// use std::sync::Arc;
+///
+/// let width: &i32 = &600;
+/// let src: Arc<str> = "https://example.com/logo.png".into();
+/// let image = view! { <mj-image src={src} width={width} /> };
+/// assert_eq!(
+///     image.to_mjml(),
+///     r#"<mj-image src="https://example.com/logo.png" width="600" />"#,
+/// );
+/// ```
+pub trait IntoAttributeValue {
+    fn into_attribute_value(self) -> Option<String>;
+
+    /// Converts the value for an attribute that mrml writes into CSS, which
+    /// is left unescaped.
+    #[doc(hidden)]
+    fn into_css_value(self) -> Option<String>
+    where
+        Self: Sized,
+    {
+        self.into_attribute_value()
+    }
+}
+
+impl IntoAttributeValue for String {
+    fn into_attribute_value(self) -> Option<String> {
+        Some(escape::escape_html(&self))
+    }
+
+    fn into_css_value(self) -> Option<String> {
+        Some(self)
+    }
+}
+
+impl IntoAttributeValue for &String {
+    fn into_attribute_value(self) -> Option<String> {
+        Some(escape::escape_html(self))
+    }
+
+    fn into_css_value(self) -> Option<String> {
+        Some(self.clone())
+    }
+}
+
+impl IntoAttributeValue for &str {
+    fn into_attribute_value(self) -> Option<String> {
+        Some(escape::escape_html(self))
+    }
+
+    fn into_css_value(self) -> Option<String> {
+        Some(self.to_owned())
+    }
+}
+
+impl IntoAttributeValue for std::borrow::Cow<'_, str> {
+    fn into_attribute_value(self) -> Option<String> {
+        Some(escape::escape_html(&self))
+    }
+
+    fn into_css_value(self) -> Option<String> {
+        Some(self.into_owned())
+    }
+}
+
+impl<T: std::fmt::Display> IntoAttributeValue for escape::Raw<T> {
+    fn into_attribute_value(self) -> Option<String> {
+        Some(self.0.to_string())
+    }
+}
+
+impl IntoAttributeValue for bool {
+    fn into_attribute_value(self) -> Option<String> {
+        self.then(String::new)
+    }
+}
+
+impl<T: IntoAttributeValue> IntoAttributeValue for Option<T> {
+    fn into_attribute_value(self) -> Option<String> {
+        self.and_then(IntoAttributeValue::into_attribute_value)
+    }
+
+    fn into_css_value(self) -> Option<String> {
+        self.and_then(IntoAttributeValue::into_css_value)
+    }
+}
+
+macro_rules! into_attribute_value {
+    ($($ty:ty),*) => {
+        $(
+            impl IntoAttributeValue for $ty {
+                fn into_attribute_value(self) -> Option<String> {
+                    Some(self.to_string())
+                }
+            }
+        )*
+    };
+}
+
+into_attribute_value!(
+    char, i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64
+);
+
+/// An attribute value in [`view!`], converted with [`IntoAttributeValue`] if
+/// it implements it, and through `Display` otherwise.
+///
+/// Calling `attribute_value` with both [`ViaIntoAttributeValue`] and
+/// [`ViaDisplay`] in scope picks the first, which is implemented on the value
+/// itself, over the second, which is only found by auto-referencing it.
+#[doc(hidden)]
+pub struct AttributeValue<T>(pub T);
+
+#[doc(hidden)]
+pub trait ViaIntoAttributeValue {
+    fn attribute_value(self) -> Option<String>;
+    fn css_value(self) -> Option<String>;
+}
+
+impl<T: IntoAttributeValue> ViaIntoAttributeValue for AttributeValue<T> {
+    fn attribute_value(self) -> Option<String> {
+        self.0.into_attribute_value()
+    }
+
+    fn css_value(self) -> Option<String> {
+        self.0.into_css_value()
+    }
+}
+
+#[doc(hidden)]
+pub trait ViaDisplay {
+    fn attribute_value(self) -> Option<String>;
+    fn css_value(self) -> Option<String>;
+}
+
+impl<T: std::fmt::Display> ViaDisplay for &AttributeValue<T> {
+    fn attribute_value(self) -> Option<String> {
+        Some(escape::escape_html(&self.0.to_string()))
+    }
+
+    fn css_value(self) -> Option<String> {
+        Some(self.0.to_string())
+    }
 }
 
 impl<T> WithAttribute for mrml::node::Node<T> {
@@ -222,6 +1018,27 @@ impl<T> WithAttribute for mrml::node::Node<T> {
     }
 }
 
+impl<T> TryWithAttribute for mrml::node::Node<T> {
+    fn try_with_attribute(self, key: String, value: String) -> Result<Self, UnsupportedAttribute> {
+        Ok(self.with_attribute(key, value))
+    }
+}
+
+/// Implements [`TryWithAttribute`] for an element that accepts any attribute.
+macro_rules! try_with_any_attribute {
+    ($el:ty) => {
+        impl TryWithAttribute for $el {
+            fn try_with_attribute(
+                self,
+                key: String,
+                value: String,
+            ) -> Result<Self, UnsupportedAttribute> {
+                Ok(self.with_attribute(key, value))
+            }
+        }
+    };
+}
+
 macro_rules! with_attribute {
     ($el:path ) => {
         impl WithAttribute for $el {
@@ -230,6 +1047,8 @@ macro_rules! with_attribute {
                 self
             }
         }
+
+        try_with_any_attribute!($el);
     };
 }
 
@@ -244,6 +1063,7 @@ with_attribute!(mrml::mj_hero::MjHero);
 with_attribute!(mrml::mj_table::MjTable);
 with_attribute!(mrml::mj_wrapper::MjWrapper);
 with_attribute!(mrml::mj_accordion::MjAccordion);
+with_attribute!(mrml::mj_accordion_element::MjAccordionElement);
 
 with_attribute!(mrml::mj_accordion_text::MjAccordionText);
 with_attribute!(mrml::mj_navbar_link::MjNavbarLink);
@@ -277,6 +1097,9 @@ impl WithAttribute for mrml::mj_attributes_element::MjAttributesElement {
     }
 }
 
+try_with_any_attribute!(mrml::mj_attributes_class::MjAttributesClass);
+try_with_any_attribute!(mrml::mj_attributes_element::MjAttributesElement);
+
 with_attribute!(mrml::mj_carousel_image::MjCarouselImage);
 with_attribute!(mrml::mj_image::MjImage);
 with_attribute!(mrml::mj_divider::MjDivider);
@@ -284,40 +1107,187 @@ with_attribute!(mrml::mj_spacer::MjSpacer);
 
 impl WithAttribute for mrml::mj_breakpoint::MjBreakpoint {
     fn with_attribute(mut self, key: String, value: String) -> Self {
-        if key == "width" {
-            self.attributes.width = value;
-            return self;
+        match key.as_str() {
+            "width" => self.attributes.width = value,
+            _ => unsupported_attribute("mj-breakpoint", key),
         }
-        // self.attributes.insert(key, value);
         self
     }
 }
 
 impl WithAttribute for mrml::mj_style::MjStyle {
     fn with_attribute(mut self, key: String, value: String) -> Self {
-        if key == "inline" {
-            self.attributes.inline = Some(value);
-            return self;
+        match key.as_str() {
+            "inline" => self.attributes.inline = Some(value),
+            _ => unsupported_attribute("mj-style", key),
         }
-        // self.attributes.insert(key, value);
         self
     }
 }
 
 impl WithAttribute for mrml::mj_font::MjFont {
     fn with_attribute(mut self, key: String, value: String) -> Self {
-        if key == "name" {
-            self.attributes.name = value;
-            return self;
+        match key.as_str() {
+            "name" => self.attributes.name = value,
+            "href" => self.attributes.href = value,
+            _ => unsupported_attribute("mj-font", key),
         }
-        if key == "href" {
-            self.attributes.href = value;
-            return self;
+        self
+    }
+}
+
+impl WithAttribute for mrml::mjml::Mjml {
+    fn with_attribute(mut self, key: String, value: String) -> Self {
+        match key.as_str() {
+            "lang" => self.attributes.lang = Some(value),
+            "dir" => self.attributes.dir = Some(value),
+            "owa" => self.attributes.owa = Some(value),
+            _ => unsupported_attribute("mjml", key),
+        }
+        self
+    }
+}
+
+impl WithAttribute for mrml::mj_include::body::MjIncludeBody {
+    fn with_attribute(mut self, key: String, value: String) -> Self {
+        use mrml::mj_include::body::MjIncludeBodyKind;
+
+        match (key.as_str(), value.as_str()) {
+            ("path", _) => self.attributes.path = value,
+            ("type", "mjml") => self.attributes.kind = MjIncludeBodyKind::Mjml,
+            ("type", "html") => self.attributes.kind = MjIncludeBodyKind::Html,
+            ("type", _) => {}
+            _ => unsupported_attribute("mj-include", key),
+        }
+        self
+    }
+}
+
+impl WithAttribute for mrml::mj_include::head::MjIncludeHead {
+    fn with_attribute(mut self, key: String, value: String) -> Self {
+        use mrml::mj_include::head::MjIncludeHeadKind;
+
+        match (key.as_str(), value.as_str()) {
+            ("path", _) => self.attributes.path = value,
+            ("type", "mjml") => self.attributes.kind = MjIncludeHeadKind::Mjml,
+            ("type", "html") => self.attributes.kind = MjIncludeHeadKind::Html,
+            ("type", "css") => self.attributes.kind = MjIncludeHeadKind::Css { inline: false },
+            // `type="css"` comes first.
+            ("css-inline", inline) => {
+                if let MjIncludeHeadKind::Css { .. } = self.attributes.kind {
+                    self.attributes.kind = MjIncludeHeadKind::Css {
+                        inline: inline == "inline",
+                    };
+                }
+            }
+            ("type", _) => {}
+            _ => unsupported_attribute("mj-include", key),
+        }
+        self
+    }
+}
+
+/// mrml renders `<mj-raw>` where it is written, so `position="file-start"`,
+/// which MJML accepts, has no effect.
+impl WithAttribute for mrml::mj_raw::MjRaw {
+    fn with_attribute(self, key: String, _value: String) -> Self {
+        if key != "position" {
+            unsupported_attribute("mj-raw", key);
+        }
+        self
+    }
+}
+
+impl WithAttribute for html_attributes::MjSelector {
+    fn with_attribute(mut self, key: String, value: String) -> Self {
+        match key.as_str() {
+            "path" => self.path = value,
+            _ => unsupported_attribute("mj-selector", key),
         }
-        // self.attributes.insert(key, value);
         self
     }
 }
 
-// mrml::mj_title::MjTitle
-// mrml::mj_preview::MjPreview
+impl WithAttribute for html_attributes::MjHtmlAttribute {
+    fn with_attribute(mut self, key: String, value: String) -> Self {
+        match key.as_str() {
+            "name" => self.name = value,
+            _ => unsupported_attribute("mj-html-attribute", key),
+        }
+        self
+    }
+}
+
+/// Implements [`TryWithAttribute`] for an element with typed attributes,
+/// accepting only the listed keys.
+macro_rules! try_with_typed_attribute {
+    ($el:path, $tag:literal, [$($key:literal),+]) => {
+        impl TryWithAttribute for $el {
+            fn try_with_attribute(
+                self,
+                key: String,
+                value: String,
+            ) -> Result<Self, UnsupportedAttribute> {
+                match key.as_str() {
+                    $($key)|+ => Ok(self.with_attribute(key, value)),
+                    _ => Err(UnsupportedAttribute::new($tag, key)),
+                }
+            }
+        }
+    };
+}
+
+try_with_typed_attribute!(
+    mrml::mj_breakpoint::MjBreakpoint,
+    "mj-breakpoint",
+    ["width"]
+);
+try_with_typed_attribute!(mrml::mj_style::MjStyle, "mj-style", ["inline"]);
+try_with_typed_attribute!(mrml::mj_font::MjFont, "mj-font", ["name", "href"]);
+try_with_typed_attribute!(mrml::mjml::Mjml, "mjml", ["lang", "dir", "owa"]);
+try_with_typed_attribute!(
+    mrml::mj_include::body::MjIncludeBody,
+    "mj-include",
+    ["path", "type"]
+);
+try_with_typed_attribute!(
+    mrml::mj_include::head::MjIncludeHead,
+    "mj-include",
+    ["path", "type", "css-inline"]
+);
+try_with_typed_attribute!(mrml::mj_raw::MjRaw, "mj-raw", ["position"]);
+try_with_typed_attribute!(html_attributes::MjSelector, "mj-selector", ["path"]);
+try_with_typed_attribute!(
+    html_attributes::MjHtmlAttribute,
+    "mj-html-attribute",
+    ["name"]
+);
+
+/// Implements [`TryWithAttribute`] and [`WithAttribute`] for an element that
+/// has no attributes at all.
+macro_rules! without_attributes {
+    ($el:path, $tag:literal) => {
+        impl WithAttribute for $el {
+            fn with_attribute(self, key: String, _value: String) -> Self {
+                unsupported_attribute($tag, key);
+                self
+            }
+        }
+
+        impl TryWithAttribute for $el {
+            fn try_with_attribute(
+                self,
+                key: String,
+                _value: String,
+            ) -> Result<Self, UnsupportedAttribute> {
+                Err(UnsupportedAttribute::new($tag, key))
+            }
+        }
+    };
+}
+
+without_attributes!(mrml::mj_attributes::MjAttributes, "mj-attributes");
+without_attributes!(mrml::mj_head::MjHead, "mj-head");
+without_attributes!(html_attributes::MjHtmlAttributes, "mj-html-attributes");
+without_attributes!(mrml::mj_preview::MjPreview, "mj-preview");
+without_attributes!(mrml::mj_title::MjTitle, "mj-title");
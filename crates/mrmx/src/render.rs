@@ -0,0 +1,371 @@
+//! Printing and rendering of the nodes built by [`view!`](crate::view).
+
+use mrml::fragment::Fragment;
+use mrml::mj_attributes::MjAttributes;
+use mrml::mj_body::MjBodyChild;
+use mrml::mj_head::{MjHead, MjHeadChild};
+use mrml::mjml::Mjml;
+use mrml::prelude::print::Printable;
+#[cfg(feature = "render")]
+use mrml::prelude::render::RenderOptions;
+
+#[cfg(feature = "render")]
+use crate::html_attributes::HtmlAttributesError;
+use crate::html_attributes::{MjHtmlAttribute, MjHtmlAttributes, MjSelector};
+use crate::url_policy::{CheckUrls, UnsafeUrl, UrlPolicy};
+#[cfg(feature = "render")]
+use crate::WithChildren;
+
+/// Output of a node built by [`view!`](crate::view) as MJML or HTML, without
+/// picking the mrml function for its type.
+///
+/// ```
+/// use mrmx::{view, Render};
+///
+/// let text = view! { <mj-text>"Hello"</mj-text> };
+/// assert_eq!(text.to_mjml(), "<mj-text>Hello</mj-text>");
+/// ```
+pub trait Render {
+    /// Prints the node as MJML.
+    fn to_mjml(&self) -> String;
+
+    /// Renders the node to an HTML email, after checking its URLs against
+    /// the [`rendering`](crate::url_policy::rendering) policy. A node other
+    /// than `<mjml>` is rendered as the only content of a document, inside the
+    /// `<mj-body>` or `<mj-head>` it belongs to.
+    #[cfg(feature = "render")]
+    fn to_html(&self, options: &RenderOptions) -> Result<String, RenderError> {
+        self.to_html_with(options, &crate::url_policy::rendering())
+    }
+
+    /// Renders the node to an HTML email, after checking its URLs against
+    /// `policy`.
+    #[cfg(feature = "render")]
+    fn to_html_with(
+        &self,
+        options: &RenderOptions,
+        policy: &UrlPolicy,
+    ) -> Result<String, RenderError>;
+
+    /// Checks the URLs in `href`, `src` and similar attributes against
+    /// `policy`, including those set at runtime, like rendering does.
+    fn check_urls(&self, policy: &UrlPolicy) -> Result<(), UnsafeUrl>;
+}
+
+/// Why [`Render::to_html`] failed.
+#[cfg(feature = "render")]
+#[derive(Debug)]
+pub enum RenderError {
+    /// mrml could not render the document.
+    Mrml(mrml::prelude::render::Error),
+    /// The `<mj-html-attributes>` of the document could not be applied.
+    HtmlAttributes(HtmlAttributesError),
+    /// A URL is not allowed by the policy.
+    UnsafeUrl(UnsafeUrl),
+}
+
+#[cfg(feature = "render")]
+impl From<mrml::prelude::render::Error> for RenderError {
+    fn from(value: mrml::prelude::render::Error) -> Self {
+        RenderError::Mrml(value)
+    }
+}
+
+#[cfg(feature = "render")]
+impl From<HtmlAttributesError> for RenderError {
+    fn from(value: HtmlAttributesError) -> Self {
+        RenderError::HtmlAttributes(value)
+    }
+}
+
+#[cfg(feature = "render")]
+impl From<UnsafeUrl> for RenderError {
+    fn from(value: UnsafeUrl) -> Self {
+        RenderError::UnsafeUrl(value)
+    }
+}
+
+#[cfg(feature = "render")]
+impl std::fmt::Display for RenderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RenderError::Mrml(error) => error.fmt(f),
+            RenderError::HtmlAttributes(error) => error.fmt(f),
+            RenderError::UnsafeUrl(error) => error.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "render")]
+impl std::error::Error for RenderError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RenderError::Mrml(error) => Some(error),
+            RenderError::HtmlAttributes(error) => Some(error),
+            RenderError::UnsafeUrl(error) => Some(error),
+        }
+    }
+}
+
+fn print<T: Printable>(node: &T) -> String {
+    node.print_dense()
+        .expect("printing to a string cannot fail")
+}
+
+#[cfg(feature = "render")]
+fn in_body(children: Vec<MjBodyChild>) -> Mjml {
+    let body = mrml::mj_body::MjBody::default().with_children(children);
+    Mjml::default().with_children(vec![body.into()])
+}
+
+#[cfg(feature = "render")]
+fn in_head(children: Vec<MjHeadChild>) -> Mjml {
+    let head = MjHead::default().with_children(children);
+    Mjml::default().with_children(vec![head.into()])
+}
+
+/// Checks the URLs of `mjml` against `policy`, renders it with mrml, then
+/// applies the `<mj-html-attributes>` of its head to the HTML.
+#[cfg(feature = "render")]
+fn render_html(
+    mjml: Mjml,
+    options: &RenderOptions,
+    policy: &UrlPolicy,
+) -> Result<String, RenderError> {
+    mjml.check_tree(policy)?;
+    let html = mjml.render(options)?;
+    let html_attributes = mjml
+        .children
+        .head
+        .as_ref()
+        .map(MjHtmlAttributes::in_head)
+        .unwrap_or_default();
+    html_attributes
+        .iter()
+        .try_fold(html, |html, attributes| attributes.apply(&html))
+        .map_err(RenderError::from)
+}
+
+/// Whether `child` carries an `<mj-html-attributes>`, which mrml would print
+/// as the `<mj-attributes>` carrying it.
+fn is_carrier(child: &MjHeadChild) -> bool {
+    matches!(
+        child,
+        MjHeadChild::MjAttributes(attributes) if MjHtmlAttributes::from_carrier(attributes).is_some()
+    )
+}
+
+fn print_attributes(attributes: &MjAttributes) -> String {
+    match MjHtmlAttributes::from_carrier(attributes) {
+        Some(html_attributes) => print(&html_attributes.to_node()),
+        None => print(attributes),
+    }
+}
+
+fn print_head_child(child: &MjHeadChild) -> String {
+    match child {
+        MjHeadChild::MjAttributes(attributes) => print_attributes(attributes),
+        child => print(child),
+    }
+}
+
+fn print_head_children(fragment: &Fragment<MjHeadChild>) -> String {
+    if !fragment.children.iter().any(is_carrier) {
+        return print(fragment);
+    }
+    fragment.children.iter().map(print_head_child).collect()
+}
+
+fn print_head(head: &MjHead) -> String {
+    if !head.children.iter().any(is_carrier) {
+        return print(head);
+    }
+    let children = head
+        .children
+        .iter()
+        .map(print_head_child)
+        .collect::<String>();
+    format!("<mj-head>{children}</mj-head>")
+}
+
+fn print_mjml(mjml: &Mjml) -> String {
+    let Some(head) = &mjml.children.head else {
+        return print(mjml);
+    };
+    if !head.children.iter().any(is_carrier) {
+        return print(mjml);
+    }
+    let attributes = [
+        ("owa", &mjml.attributes.owa),
+        ("lang", &mjml.attributes.lang),
+        ("dir", &mjml.attributes.dir),
+    ]
+    .into_iter()
+    .filter_map(|(name, value)| Some(format!(" {name}=\"{}\"", value.as_ref()?)))
+    .collect::<String>();
+    let body = mjml.children.body.as_ref().map(print).unwrap_or_default();
+    format!("<mjml{attributes}>{}{body}</mjml>", print_head(head))
+}
+
+/// Implements [`Render`] for each type, rendering the document built by the
+/// given closure.
+macro_rules! render {
+    ($($el:ty => $document:expr;)*) => {
+        $(
+            render!(@impl $el, print, $document);
+        )*
+    };
+    (@impl $el:ty, $print:expr, $document:expr) => {
+        impl Render for $el {
+            fn to_mjml(&self) -> String {
+                let print: fn(&$el) -> String = $print;
+                print(self)
+            }
+
+            #[cfg(feature = "render")]
+            fn to_html_with(
+                &self,
+                options: &RenderOptions,
+                policy: &UrlPolicy,
+            ) -> Result<String, RenderError> {
+                let document: fn(&$el) -> Mjml = $document;
+                render_html(document(self), options, policy)
+            }
+
+            fn check_urls(&self, policy: &UrlPolicy) -> Result<(), UnsafeUrl> {
+                self.check_tree(policy)
+            }
+        }
+    };
+}
+
+/// Implements [`Render`] for elements that go in `<mj-body>`.
+macro_rules! render_body {
+    ($($el:ty),* $(,)?) => {
+        render! {
+            $($el => |node| in_body(vec![node.clone().into()]);)*
+        }
+    };
+}
+
+/// Implements [`Render`] for elements that go in `<mj-head>`.
+macro_rules! render_head {
+    ($($el:ty),* $(,)?) => {
+        render! {
+            $($el => |node| in_head(vec![node.clone().into()]);)*
+        }
+    };
+}
+
+// `<mj-html-attributes>` is carried by an `<mj-attributes>` in the head, which
+// is printed as the element it carries.
+render!(@impl Mjml, print_mjml, Mjml::clone);
+render!(@impl MjHead, print_head, |node| {
+    Mjml::default().with_children(vec![node.clone().into()])
+});
+render!(@impl Fragment<MjHeadChild>, print_head_children, |node| {
+    in_head(node.children.clone())
+});
+render!(@impl MjHeadChild, print_head_child, |node| in_head(vec![node.clone()]));
+render!(@impl MjAttributes, print_attributes, |node| in_head(vec![node.clone().into()]));
+render!(@impl MjHtmlAttributes, |node| print(&node.to_node()), |node| {
+    in_head(vec![node.clone().into()])
+});
+render!(@impl MjSelector, |node| print(&node.to_node()), |node| {
+    let attributes = MjHtmlAttributes::default().with_children(vec![node.clone().into()]);
+    in_head(vec![attributes.into()])
+});
+render!(@impl MjHtmlAttribute, |node| print(&node.to_node()), |node| {
+    let selector = MjSelector::default().with_children(vec![node.clone().into()]);
+    let attributes = MjHtmlAttributes::default().with_children(vec![selector.into()]);
+    in_head(vec![attributes.into()])
+});
+
+render! {
+    mrml::mj_body::MjBody => |node| Mjml::default().with_children(vec![node.clone().into()]);
+    Fragment<MjBodyChild> => |node| in_body(node.children.clone());
+    mrml::node::Node<mrml::mj_raw::MjRawChild> => |node| {
+        let raw = mrml::mj_raw::MjRaw::default().with_children(vec![node.clone().into()]);
+        in_body(vec![raw.into()])
+    };
+    mrml::mj_accordion_element::MjAccordionElement => |node| {
+        let accordion =
+            mrml::mj_accordion::MjAccordion::default().with_children(vec![node.clone().into()]);
+        in_body(vec![accordion.into()])
+    };
+    mrml::mj_accordion_text::MjAccordionText => |node| {
+        let element = mrml::mj_accordion_element::MjAccordionElement::default()
+            .with_children(vec![node.clone().into()]);
+        let accordion =
+            mrml::mj_accordion::MjAccordion::default().with_children(vec![element.into()]);
+        in_body(vec![accordion.into()])
+    };
+    mrml::mj_accordion_title::MjAccordionTitle => |node| {
+        let element = mrml::mj_accordion_element::MjAccordionElement::default()
+            .with_children(vec![node.clone().into()]);
+        let accordion =
+            mrml::mj_accordion::MjAccordion::default().with_children(vec![element.into()]);
+        in_body(vec![accordion.into()])
+    };
+    mrml::mj_carousel_image::MjCarouselImage => |node| {
+        let carousel =
+            mrml::mj_carousel::MjCarousel::default().with_children(vec![node.clone().into()]);
+        in_body(vec![carousel.into()])
+    };
+    mrml::mj_navbar_link::MjNavbarLink => |node| {
+        let navbar = mrml::mj_navbar::MjNavbar::default().with_children(vec![node.clone().into()]);
+        in_body(vec![navbar.into()])
+    };
+    mrml::mj_social_element::MjSocialElement => |node| {
+        let social = mrml::mj_social::MjSocial::default().with_children(vec![node.clone().into()]);
+        in_body(vec![social.into()])
+    };
+    mrml::mj_attributes_all::MjAttributesAll => |node| {
+        let attributes =
+            mrml::mj_attributes::MjAttributes::default().with_children(vec![node.clone().into()]);
+        in_head(vec![attributes.into()])
+    };
+    mrml::mj_attributes_class::MjAttributesClass => |node| {
+        let attributes =
+            mrml::mj_attributes::MjAttributes::default().with_children(vec![node.clone().into()]);
+        in_head(vec![attributes.into()])
+    };
+    mrml::mj_attributes_element::MjAttributesElement => |node| {
+        let attributes =
+            mrml::mj_attributes::MjAttributes::default().with_children(vec![node.clone().into()]);
+        in_head(vec![attributes.into()])
+    };
+}
+
+render_body!(
+    MjBodyChild,
+    mrml::comment::Comment,
+    mrml::text::Text,
+    mrml::node::Node<MjBodyChild>,
+    mrml::mj_accordion::MjAccordion,
+    mrml::mj_button::MjButton,
+    mrml::mj_carousel::MjCarousel,
+    mrml::mj_column::MjColumn,
+    mrml::mj_divider::MjDivider,
+    mrml::mj_group::MjGroup,
+    mrml::mj_hero::MjHero,
+    mrml::mj_image::MjImage,
+    mrml::mj_include::body::MjIncludeBody,
+    mrml::mj_navbar::MjNavbar,
+    mrml::mj_raw::MjRaw,
+    mrml::mj_section::MjSection,
+    mrml::mj_social::MjSocial,
+    mrml::mj_spacer::MjSpacer,
+    mrml::mj_table::MjTable,
+    mrml::mj_text::MjText,
+    mrml::mj_wrapper::MjWrapper,
+);
+
+render_head!(
+    mrml::mj_breakpoint::MjBreakpoint,
+    mrml::mj_font::MjFont,
+    mrml::mj_include::head::MjIncludeHead,
+    mrml::mj_preview::MjPreview,
+    mrml::mj_style::MjStyle,
+    mrml::mj_title::MjTitle,
+);
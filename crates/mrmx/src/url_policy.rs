@@ -0,0 +1,434 @@
+//! Checks on the URLs in `href`, `src`, `background-url` and similar
+//! attributes, so that a bad link fails rather than reaching customers.
+//!
+//! By default, only absolute `https://` URLs to public hosts are accepted:
+//! `javascript:` and `data:` URLs, relative URLs, and links to `localhost` or
+//! private networks, which tend to leak from staging, are all rejected.
+//!
+//! [`view!`](crate::view) checks literal URLs at compile time, and
+//! [`Render::to_html`] checks every URL in the tree before rendering,
+//! including those set at runtime. The compile-time checks can be loosened for
+//! a view with `#![allowed_urls(..)]`, listing extra schemes like `mailto:`,
+//! `relative` and `private-hosts`:
+//!
+//! ```
+//! # use mrmx::view;
+//! view! {
+//!     #![allowed_urls("mailto:")]
+//!     <mj-button href="mailto:help@example.com">"Contact us"</mj-button>
+//! };
+//! ```
+//!
+//! The same entries loosen the checks of the whole build in the
+//! `MRMX_ALLOWED_URLS` environment variable:
+//!
+//! ```toml
+//! [env]
+//! MRMX_ALLOWED_URLS = "mailto:, tel:, relative"
+//! ```
+//!
+//! Neither reaches [`Render::to_html`], which cannot know what a view allowed.
+//! It checks against the [`rendering`] policy instead, which also accepts the
+//! `mailto:` and `tel:` links and relative URLs that are common in emails, so
+//! the view above renders as it is. A view allowing other URLs, like
+//! `private-hosts`, is rendered with [`Render::to_html_with`] and a policy
+//! allowing the same URLs.
+//!
+//! [`Render::check_urls`] checks a node against any policy:
+//!
+//! ```
+//! # use mrmx::view;
+//! use mrmx::url_policy::UrlPolicy;
+//! use mrmx::Render;
+//!
+//! let url = "javascript:alert(1)";
+//! let button = view! { <mj-button href={url}>"Buy"</mj-button> };
+//! let err = button.check_urls(&UrlPolicy::default()).unwrap_err();
+//! assert_eq!(
+//!     err.to_string(),
+//!     "unsafe `href` on <mj-button>: `javascript:` URLs are not allowed",
+//! );
+//! ```
+//!
+//! [`Render::check_urls`]: crate::Render::check_urls
+//! [`Render::to_html`]: crate::Render
+//! [`Render::to_html_with`]: crate::Render
+
+use mrml::comment::Comment;
+use mrml::fragment::Fragment;
+use mrml::mj_accordion::MjAccordionChild;
+use mrml::mj_attributes::MjAttributesChild;
+use mrml::mj_body::MjBodyChild;
+use mrml::mj_carousel::MjCarouselChild;
+use mrml::mj_head::MjHeadChild;
+use mrml::mj_include::body::MjIncludeBodyChild;
+use mrml::mj_include::head::MjIncludeHeadChild;
+use mrml::mj_navbar::MjNavbarChild;
+use mrml::mj_raw::MjRawChild;
+use mrml::mj_social::MjSocialChild;
+use mrml::node::Node;
+use mrml::prelude::hash::Map;
+use mrml::prelude::print::Printable;
+use mrml::text::Text;
+use mrmx_markup::url::decode;
+pub use mrmx_markup::url::{is_url_attribute, UrlPolicy, UrlProblem};
+
+use crate::html_attributes::{
+    MjHtmlAttribute, MjHtmlAttributes, MjHtmlAttributesChild, MjSelector, MjSelectorChild,
+};
+
+/// The policy that [`Render::to_html`](crate::Render) enforces: the default
+/// one, also accepting `mailto:` and `tel:` links and relative URLs.
+/// `#![allowed_urls(..)]` and `MRMX_ALLOWED_URLS` only loosen the checks of
+/// [`view!`](crate::view), and have no effect on it.
+pub fn rendering() -> UrlPolicy {
+    UrlPolicy::allowing("mailto:, tel:, relative")
+}
+
+/// A URL attribute rejected by a [`UrlPolicy`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnsafeUrl {
+    /// The tag of the element, like `mj-button`.
+    pub element: String,
+    pub attribute: String,
+    pub url: String,
+    pub problem: UrlProblem,
+}
+
+impl std::fmt::Display for UnsafeUrl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "unsafe `{}` on <{}>: {}",
+            self.attribute, self.element, self.problem
+        )
+    }
+}
+
+impl std::error::Error for UnsafeUrl {}
+
+/// A node whose URLs are checked by walking its tree, so that the values of
+/// typed attributes are checked as they are, rather than as printed.
+pub(crate) trait CheckUrls {
+    fn check_tree(&self, policy: &UrlPolicy) -> Result<(), UnsafeUrl>;
+}
+
+/// Checks the attribute `name` of `<element>`, if it holds a URL.
+fn check_attribute(
+    policy: &UrlPolicy,
+    element: &str,
+    name: &str,
+    value: &str,
+) -> Result<(), UnsafeUrl> {
+    if !is_url_attribute(name) {
+        return Ok(());
+    }
+    let url = decode(value);
+    policy.check(&url).map_err(|problem| UnsafeUrl {
+        element: element.to_string(),
+        attribute: name.to_string(),
+        url,
+        problem,
+    })
+}
+
+fn check_attributes(
+    policy: &UrlPolicy,
+    element: &str,
+    attributes: &Map<String, String>,
+) -> Result<(), UnsafeUrl> {
+    attributes
+        .iter()
+        .try_for_each(|(name, value)| check_attribute(policy, element, name, value))
+}
+
+fn check_children<T: CheckUrls>(policy: &UrlPolicy, children: &[T]) -> Result<(), UnsafeUrl> {
+    children
+        .iter()
+        .try_for_each(|child| child.check_tree(policy))
+}
+
+/// Checks the start tags in `html`. Markup in comments is checked too, since
+/// Outlook renders the contents of conditional comments.
+fn check_html(policy: &UrlPolicy, html: &str) -> Result<(), UnsafeUrl> {
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start..];
+        let Some((element, len)) = Element::parse(rest) else {
+            rest = &rest[1..];
+            continue;
+        };
+        rest = &rest[len..];
+        for (name, value) in &element.attributes {
+            if let Some(value) = value {
+                check_attribute(policy, &element.tag, name, value)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Implements [`CheckUrls`] for elements with an `attributes` map, and the
+/// given field of children.
+macro_rules! check_element {
+    ($($el:ty => $tag:literal $(, $children:ident)?;)*) => {
+        $(
+            impl CheckUrls for $el {
+                fn check_tree(&self, policy: &UrlPolicy) -> Result<(), UnsafeUrl> {
+                    check_attributes(policy, $tag, &self.attributes)?;
+                    $(check_children(policy, &self.$children)?;)?
+                    Ok(())
+                }
+            }
+        )*
+    };
+}
+
+/// Implements [`CheckUrls`] for elements without URL attributes, and the given
+/// field of children.
+macro_rules! check_children {
+    ($($el:ty $(, $children:ident)?;)*) => {
+        $(
+            impl CheckUrls for $el {
+                fn check_tree(&self, _policy: &UrlPolicy) -> Result<(), UnsafeUrl> {
+                    $(check_children(_policy, &self.$children)?;)?
+                    Ok(())
+                }
+            }
+        )*
+    };
+}
+
+/// Implements [`CheckUrls`] for enums of children, whose variants each hold a
+/// node.
+macro_rules! check_variants {
+    ($($enum:ident { $($variant:ident),* $(,)? })*) => {
+        $(
+            impl CheckUrls for $enum {
+                fn check_tree(&self, policy: &UrlPolicy) -> Result<(), UnsafeUrl> {
+                    match self {
+                        $($enum::$variant(node) => node.check_tree(policy),)*
+                    }
+                }
+            }
+        )*
+    };
+}
+
+check_element! {
+    mrml::mj_accordion::MjAccordion => "mj-accordion", children;
+    mrml::mj_accordion_text::MjAccordionText => "mj-accordion-text", children;
+    mrml::mj_accordion_title::MjAccordionTitle => "mj-accordion-title", children;
+    mrml::mj_attributes_all::MjAttributesAll => "mj-all";
+    mrml::mj_attributes_class::MjAttributesClass => "mj-class";
+    mrml::mj_body::MjBody => "mj-body", children;
+    mrml::mj_button::MjButton => "mj-button", children;
+    mrml::mj_carousel::MjCarousel => "mj-carousel", children;
+    mrml::mj_carousel_image::MjCarouselImage => "mj-carousel-image";
+    mrml::mj_column::MjColumn => "mj-column", children;
+    mrml::mj_divider::MjDivider => "mj-divider";
+    mrml::mj_group::MjGroup => "mj-group", children;
+    mrml::mj_hero::MjHero => "mj-hero", children;
+    mrml::mj_image::MjImage => "mj-image";
+    mrml::mj_navbar::MjNavbar => "mj-navbar", children;
+    mrml::mj_navbar_link::MjNavbarLink => "mj-navbar-link", children;
+    mrml::mj_section::MjSection => "mj-section", children;
+    mrml::mj_social::MjSocial => "mj-social", children;
+    mrml::mj_social_element::MjSocialElement => "mj-social-element", children;
+    mrml::mj_spacer::MjSpacer => "mj-spacer";
+    mrml::mj_table::MjTable => "mj-table", children;
+    mrml::mj_text::MjText => "mj-text", children;
+    mrml::mj_wrapper::MjWrapper => "mj-wrapper", children;
+}
+
+check_children! {
+    mrml::mj_breakpoint::MjBreakpoint;
+    mrml::mj_head::MjHead, children;
+    mrml::mj_include::body::MjIncludeBody, children;
+    mrml::mj_include::head::MjIncludeHead, children;
+    mrml::mj_preview::MjPreview;
+    mrml::mj_raw::MjRaw, children;
+    mrml::mj_style::MjStyle;
+    mrml::mj_title::MjTitle;
+    MjHtmlAttributes, children;
+    MjSelector, children;
+}
+
+check_variants! {
+    MjAccordionChild { Comment, MjAccordionElement }
+    MjAttributesChild { MjAttributesAll, MjAttributesClass, MjAttributesElement }
+    MjBodyChild {
+        Comment, MjAccordion, MjButton, MjCarousel, MjColumn, MjDivider, MjGroup, MjHero,
+        MjInclude, MjImage, MjNavbar, MjRaw, MjSection, MjSocial, MjSpacer, MjTable, MjText,
+        MjWrapper, Node, Text,
+    }
+    MjCarouselChild { Comment, MjCarouselImage }
+    MjHeadChild {
+        Comment, MjAttributes, MjBreakpoint, MjFont, MjInclude, MjPreview, MjRaw, MjStyle,
+        MjTitle,
+    }
+    MjIncludeBodyChild {
+        Comment, MjAccordion, MjButton, MjCarousel, MjColumn, MjDivider, MjGroup, MjHero,
+        MjImage, MjNavbar, MjRaw, MjSection, MjSocial, MjSpacer, MjTable, MjText, MjWrapper,
+        Node, Text,
+    }
+    MjHtmlAttributesChild { Comment, MjSelector }
+    MjIncludeHeadChild {
+        Comment, MjAttributes, MjBreakpoint, MjFont, MjPreview, MjRaw, MjStyle, MjTitle, Text,
+    }
+    MjNavbarChild { Comment, MjNavbarLink }
+    MjRawChild { Comment, Node, Text }
+    MjSelectorChild { Comment, MjHtmlAttribute }
+    MjSocialChild { Comment, MjSocialElement }
+}
+
+// Text and comments hold HTML, like that passed to `raw`.
+impl CheckUrls for Text {
+    fn check_tree(&self, policy: &UrlPolicy) -> Result<(), UnsafeUrl> {
+        check_html(policy, &print(self))
+    }
+}
+
+impl CheckUrls for Comment {
+    fn check_tree(&self, policy: &UrlPolicy) -> Result<(), UnsafeUrl> {
+        check_html(policy, &print(self))
+    }
+}
+
+fn print<T: Printable>(node: &T) -> String {
+    node.print_dense()
+        .expect("printing to a string cannot fail")
+}
+
+impl<T: CheckUrls> CheckUrls for Node<T> {
+    fn check_tree(&self, policy: &UrlPolicy) -> Result<(), UnsafeUrl> {
+        check_attributes(policy, &self.tag, &self.attributes)?;
+        check_children(policy, &self.children)
+    }
+}
+
+impl<T: CheckUrls> CheckUrls for Fragment<T> {
+    fn check_tree(&self, policy: &UrlPolicy) -> Result<(), UnsafeUrl> {
+        check_children(policy, &self.children)
+    }
+}
+
+impl CheckUrls for mrml::mjml::Mjml {
+    fn check_tree(&self, policy: &UrlPolicy) -> Result<(), UnsafeUrl> {
+        if let Some(head) = &self.children.head {
+            head.check_tree(policy)?;
+        }
+        match &self.children.body {
+            Some(body) => body.check_tree(policy),
+            None => Ok(()),
+        }
+    }
+}
+
+impl CheckUrls for mrml::mj_accordion_element::MjAccordionElement {
+    fn check_tree(&self, policy: &UrlPolicy) -> Result<(), UnsafeUrl> {
+        check_attributes(policy, "mj-accordion-element", &self.attributes)?;
+        if let Some(title) = &self.children.title {
+            title.check_tree(policy)?;
+        }
+        match &self.children.text {
+            Some(text) => text.check_tree(policy),
+            None => Ok(()),
+        }
+    }
+}
+
+impl CheckUrls for mrml::mj_attributes_element::MjAttributesElement {
+    fn check_tree(&self, policy: &UrlPolicy) -> Result<(), UnsafeUrl> {
+        check_attributes(policy, &self.name, &self.attributes)
+    }
+}
+
+impl CheckUrls for mrml::mj_font::MjFont {
+    fn check_tree(&self, policy: &UrlPolicy) -> Result<(), UnsafeUrl> {
+        check_attribute(policy, "mj-font", "href", &self.attributes.href)
+    }
+}
+
+// The value is set on the HTML as is, where its references are decoded.
+impl CheckUrls for MjHtmlAttribute {
+    fn check_tree(&self, policy: &UrlPolicy) -> Result<(), UnsafeUrl> {
+        check_attribute(policy, "mj-html-attribute", &self.name, &self.value)
+    }
+}
+
+// The `<mj-html-attributes>` it may carry is checked as itself.
+impl CheckUrls for mrml::mj_attributes::MjAttributes {
+    fn check_tree(&self, policy: &UrlPolicy) -> Result<(), UnsafeUrl> {
+        match MjHtmlAttributes::from_carrier(self) {
+            Some(html_attributes) => html_attributes.check_tree(policy),
+            None => check_children(policy, &self.children),
+        }
+    }
+}
+
+/// A start tag in the HTML of a text node or comment.
+struct Element {
+    tag: String,
+    /// The name and value of each attribute.
+    attributes: Vec<(String, Option<String>)>,
+}
+
+impl Element {
+    /// Parses the start tag at the beginning of `markup`, returning it with its
+    /// length.
+    fn parse(markup: &str) -> Option<(Self, usize)> {
+        let tag = tag_name(&markup[1..]);
+        if tag.is_empty() {
+            return None;
+        }
+        let mut element = Element {
+            tag: tag.to_ascii_lowercase(),
+            attributes: Vec::new(),
+        };
+        let mut rest = &markup[1 + tag.len()..];
+        loop {
+            rest = rest.trim_start();
+            if let Some(after) = rest.strip_prefix("/>").or_else(|| rest.strip_prefix('>')) {
+                return Some((element, markup.len() - after.len()));
+            }
+            let name_len =
+                rest.find(|c: char| c.is_whitespace() || matches!(c, '=' | '>' | '/'))?;
+            if name_len == 0 {
+                // A stray `/` or `=`.
+                rest = &rest[1..];
+                continue;
+            }
+            let name = rest[..name_len].to_ascii_lowercase();
+            rest = &rest[name_len..];
+            let value = match rest.trim_start().strip_prefix('=') {
+                Some(after) => {
+                    let after = after.trim_start();
+                    let (value, len) = match after.chars().next() {
+                        Some(quote @ ('"' | '\'')) => {
+                            let end = after[1..].find(quote)? + 1;
+                            (&after[1..end], end + 1)
+                        }
+                        _ => {
+                            let end = after
+                                .find(|c: char| c.is_whitespace() || c == '>')
+                                .unwrap_or(after.len());
+                            (&after[..end], end)
+                        }
+                    };
+                    rest = &after[len..];
+                    Some(value.to_string())
+                }
+                None => None,
+            };
+            element.attributes.push((name, value));
+        }
+    }
+}
+
+fn tag_name(markup: &str) -> &str {
+    let len = markup
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+        .unwrap_or(markup.len());
+    &markup[..len]
+}
@@ -1,6 +1,6 @@
 use expect_test::expect;
 use mrml::mj_body::MjBodyChild;
-use mrmx::{view, WithAttribute};
+use mrmx::view;
 
 #[test]
 fn basic_html() {
@@ -10,6 +10,19 @@ fn basic_html() {
     expected.assert_eq(&format!("{actual:?}"))
 }
 
+#[test]
+fn custom_tags() {
+    let expected = expect![[
+        r#"Node { tag: "mj-chart", attributes: Map({"data-kind": "bar"}), children: [] }"#
+    ]];
+    let actual: mrml::node::Node<mrml::mj_body::MjBodyChild> = view! {
+        #![custom_tags("mj-chart")]
+        <mj-chart data-kind="bar"></mj-chart>
+    };
+
+    expected.assert_eq(&format!("{actual:?}"))
+}
+
 #[test]
 fn mjml() {
     let expected = expect!["Mjml { attributes: MjmlAttributes { owa: None, lang: None, dir: None }, children: MjmlChildren { head: None, body: None } }"];
@@ -18,6 +31,174 @@ fn mjml() {
     expected.assert_eq(&format!("{actual:?}"))
 }
 
+#[test]
+fn mjml_attributes() {
+    let expected = expect![[
+        r#"Mjml { attributes: MjmlAttributes { owa: Some("desktop"), lang: Some("ar"), dir: Some("rtl") }, children: MjmlChildren { head: None, body: None } }"#
+    ]];
+    let lang = "ar";
+    let actual = view! { <mjml lang={lang} dir="rtl" owa="desktop"></mjml> };
+
+    expected.assert_eq(&format!("{actual:?}"))
+}
+
+#[test]
+fn unsupported_attribute() {
+    use mrmx::TryWithAttribute;
+
+    let expected =
+        expect![[r#"Err(UnsupportedAttribute { element: "mj-font", attribute: "nam" })"#]];
+    let actual = mrmx::mrml::mj_font::MjFont::default()
+        .try_with_attribute("nam".to_string(), "Roboto".to_string());
+
+    expected.assert_eq(&format!("{actual:?}"))
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "<mj-font> does not support the `nam` attribute")]
+fn with_attribute_reports_unsupported_attribute() {
+    use mrmx::WithAttribute;
+
+    let attributes = [("name", "Roboto"), ("nam", "Lato")];
+    mrmx::mrml::mj_font::MjFont::default().with_attributes(attributes);
+}
+
+#[test]
+fn mjml_merges_heads_and_bodies() {
+    use mrmx::mrml::mj_body::MjBody;
+    use mrmx::mrml::mj_head::MjHead;
+
+    let expected = expect![[
+        r##"Mjml { attributes: MjmlAttributes { owa: None, lang: None, dir: None }, children: MjmlChildren { head: Some(MjHead { children: [MjTitle(MjTitle { children: "Welcome" }), MjPreview(MjPreview { children: "Hello" })] }), body: Some(MjBody { attributes: Map({"background-color": "#f4f4f4", "width": "600px"}), children: [MjDivider(MjDivider { attributes: Map({}) }), MjSpacer(MjSpacer { attributes: Map({}) })] }) } }"##
+    ]];
+    let layout_head: MjHead = view! { <mj-head><mj-title>"Welcome"</mj-title></mj-head> };
+    let layout_body: MjBody = view! {
+      <mj-body background-color="#ffffff" width="600px"><mj-divider /></mj-body>
+    };
+    let actual = view! {
+      <mjml>
+        {layout_head}
+        {layout_body}
+        <mj-head><mj-preview>"Hello"</mj-preview></mj-head>
+        <mj-body background-color="#f4f4f4"><mj-spacer /></mj-body>
+      </mjml>
+    };
+
+    expected.assert_eq(&format!("{actual:?}"))
+}
+
+#[test]
+fn accordion_element_comments() {
+    use mrmx::Render;
+
+    let expected = expect!["<mj-accordion-element><mj-accordion-title><!--esp:start-->Question</mj-accordion-title><mj-accordion-text><!--esp:answer-->Answer<!--esp:end--></mj-accordion-text></mj-accordion-element>"];
+    let actual = view! {
+      <mj-accordion-element>
+        <!-- "esp:start" -->
+        <mj-accordion-title>"Question"</mj-accordion-title>
+        <mj-accordion-text><!-- "esp:answer" -->"Answer"</mj-accordion-text>
+        <!-- "esp:end" -->
+      </mj-accordion-element>
+    };
+
+    expected.assert_eq(&actual.to_mjml())
+}
+
+#[test]
+fn accordion_element_only_comments() {
+    let expected = expect!["MjAccordionElement { attributes: Map({}), children: MjAccordionElementChildren { title: None, text: None } }"];
+    let actual = view! {
+      <mj-accordion-element>
+        <!-- "esp:start" -->
+      </mj-accordion-element>
+    };
+
+    expected.assert_eq(&format!("{actual:?}"))
+}
+
+#[test]
+fn text_element_comments() {
+    let expected = expect![[
+        r#"MjStyle { attributes: MjStyleAttributes { inline: None }, children: "<!--[if mso]-->.button { color: red; }" }"#
+    ]];
+    let actual = view! {
+      <mj-style>
+        <!-- "[if mso]" -->
+        ".button { color: red; }"
+      </mj-style>
+    };
+
+    expected.assert_eq(&format!("{actual:?}"))
+}
+
+#[test]
+fn text_element_expressions() {
+    struct Order {
+        id: u32,
+    }
+
+    let expected = expect![[
+        r#"MjHead { children: [MjTitle(MjTitle { children: "Order #42 shipped" }), MjPreview(MjPreview { children: "Ada, your receipt" })] }"#
+    ]];
+    let order = Order { id: 42 };
+    let name = "Ada";
+    let actual = view! {
+      <mj-head>
+        <mj-title>"Order #"{order.id}" shipped"</mj-title>
+        <mj-preview>{name}", your receipt"</mj-preview>
+      </mj-head>
+    };
+
+    expected.assert_eq(&format!("{actual:?}"))
+}
+
+#[test]
+fn escaped_expressions() {
+    let expected = expect![[r#"
+        MjColumn { attributes: Map({}), children: [MjText(MjText { attributes: Map({}), children: [Text(Text("Hi ")), Text(Text("Tom &amp; Jerry")), Text(Text(", ")), Text(Text("<b>welcome</b>"))] }), MjButton(MjButton { attributes: Map({"href": "/search?q=&lt;b&gt;", "title": "&quot;quoted&quot;"}), children: [Text(Text("Search"))] }), MjAccordion(MjAccordion { attributes: Map({}), children: [MjAccordionElement(MjAccordionElement { attributes: Map({}), children: MjAccordionElementChildren { title: Some(MjAccordionTitle { attributes: Map({}), children: [Text("Tom &amp; Jerry"), Text(" #"), Text("7")] }), text: None } })] })] }
+        MjTitle { children: "Tom &amp; Jerry <&copy;>" }"#]];
+    let name = "Tom & Jerry";
+    let link = "/search?q=<b>";
+    let attrs = [("title", "\"quoted\"")];
+    let actual = view! {
+      <mj-column>
+        <mj-text>"Hi "{name}", "{mrmx::raw("<b>welcome</b>")}</mj-text>
+        <mj-button href={link} {..attrs}>"Search"</mj-button>
+        <mj-accordion>
+          <mj-accordion-element>
+            <mj-accordion-title>{name}" #"{Some(7)}</mj-accordion-title>
+          </mj-accordion-element>
+        </mj-accordion>
+      </mj-column>
+    };
+    let title = view! { <mj-title>{name}" <"{mrmx::raw("&copy;")}">"</mj-title> };
+
+    expected.assert_eq(&format!("{actual:?}\n{title:?}"))
+}
+
+/// Renders attributes that mrml writes into CSS without escaping them.
+#[cfg(feature = "render")]
+#[test]
+fn css_attributes_not_escaped() {
+    use mrmx::Render;
+
+    let href = "https://fonts.googleapis.com/css?family=Roboto&display=swap";
+    let email = view! {
+      <mjml>
+        <mj-head>
+          <mj-font name={"Roboto"} href={href} />
+        </mj-head>
+        <mj-body>
+          <mj-text font-family="Roboto">"Hello"</mj-text>
+        </mj-body>
+      </mjml>
+    };
+    let html = email.to_html(&Default::default()).unwrap();
+    assert!(html.contains(href), "{html}");
+    assert!(!html.contains("&amp;display"), "{html}");
+}
+
 #[test]
 fn mjml_title() {
     let expected = expect![[
@@ -100,3 +281,774 @@ fn fragment() {
     };
     expected.assert_eq(&format!("{actual:?}"))
 }
+
+#[test]
+fn attribute_expressions() {
+    let expected = expect![[
+        r#"MjButton { attributes: Map({"href": "https://example.com/orders/42", "padding": "10px"}), children: [Text(Text("View order"))] }"#
+    ]];
+    let url = format!("https://example.com/orders/{}", 42);
+    let pad = 10;
+    let actual = view! {
+      <mj-button href={url} padding={format!("{}px", pad)}>"View order"</mj-button>
+    };
+    expected.assert_eq(&format!("{actual:?}"))
+}
+
+#[test]
+fn spec_attributes() {
+    let expected = expect![[
+        r#"MjSection { attributes: Map({}), children: [MjRaw(MjRaw { children: [Comment(Comment { children: "esp:preheader" })] }), MjSocial(MjSocial { attributes: Map({}), children: [MjSocialElement(MjSocialElement { attributes: Map({"name": "github", "icon-position": "right"}), children: [Text(Text("GitHub"))] })] })] }"#
+    ]];
+    let actual = view! {
+      <mj-section>
+        <mj-raw position="file-start"><!-- "esp:preheader" --></mj-raw>
+        <mj-social>
+          <mj-social-element name="github" icon-position="right">"GitHub"</mj-social-element>
+        </mj-social>
+      </mj-section>
+    };
+    expected.assert_eq(&format!("{actual:?}"))
+}
+
+#[test]
+fn spread_attributes() {
+    let expected = expect![[
+        r##"MjButton { attributes: Map({"background-color": "#123456", "color": "white", "padding": "0"}), children: [] }"##
+    ]];
+    let brand = vec![
+        ("background-color".to_string(), "#123456".to_string()),
+        ("color".to_string(), "black".to_string()),
+    ];
+    let actual = view! {
+      <mj-button {..brand} color="white" padding="0" />
+    };
+    expected.assert_eq(&format!("{actual:?}"))
+}
+
+#[test]
+fn optional_attributes() {
+    let expected = expect![[
+        r#"MjButton { attributes: Map({"href": "https://example.com", "padding": "4"}), children: [] }"#
+    ]];
+    let href: Option<&str> = Some("https://example.com");
+    let title: Option<String> = None;
+    let actual = view! {
+      <mj-button href={href} title={title} padding={Some(4)} css-class={false} />
+    };
+    expected.assert_eq(&format!("{actual:?}"))
+}
+
+struct Pixels(u32);
+
+impl std::fmt::Display for Pixels {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}px", self.0)
+    }
+}
+
+#[test]
+fn display_attributes() {
+    let expected = expect![[
+        r#"MjButton { attributes: Map({"padding": "12px", "border": "1px solid &quot;red&quot;", "href": "https://example.com/?a=1&amp;b=2", "width": "600"}), children: [] }"#
+    ]];
+    let padding = Pixels(12);
+    let border: Box<str> = "1px solid \"red\"".into();
+    let href: std::sync::Arc<str> = "https://example.com/?a=1&b=2".into();
+    let width = 600;
+    let actual = view! {
+      <mj-button padding={padding} border={border} href={href} width={&width} />
+    };
+    expected.assert_eq(&format!("{actual:?}"))
+}
+
+struct SummaryProps {
+    heading: String,
+    children: Vec<MjBodyChild>,
+}
+
+#[derive(Default)]
+struct SummaryPropsBuilder {
+    heading: Option<String>,
+    children: Vec<MjBodyChild>,
+}
+
+impl SummaryPropsBuilder {
+    fn heading(mut self, heading: &str) -> Self {
+        self.heading = Some(heading.to_string());
+        self
+    }
+
+    fn children(mut self, children: Vec<MjBodyChild>) -> Self {
+        self.children = children;
+        self
+    }
+
+    fn build(self) -> SummaryProps {
+        SummaryProps {
+            heading: self.heading.unwrap(),
+            children: self.children,
+        }
+    }
+}
+
+impl mrmx::Props for SummaryProps {
+    type Builder = SummaryPropsBuilder;
+
+    fn builder() -> Self::Builder {
+        SummaryPropsBuilder::default()
+    }
+}
+
+#[allow(non_snake_case)]
+fn Summary(props: SummaryProps) -> mrml::mj_column::MjColumn {
+    mrmx::WithChildren::with_children(
+        view! { <mj-column><mj-text>{mrml::text::Text::from(props.heading).into()}</mj-text></mj-column> },
+        props.children,
+    )
+}
+
+#[allow(non_snake_case)]
+fn Spacer(_: ()) -> mrml::mj_spacer::MjSpacer {
+    view! { <mj-spacer height="10px" /> }
+}
+
+#[test]
+fn components() {
+    let expected = expect![[
+        r#"MjSection { attributes: Map({}), children: [MjColumn(MjColumn { attributes: Map({}), children: [MjText(MjText { attributes: Map({}), children: [Text(Text("Your order"))] }), MjText(MjText { attributes: Map({}), children: [Text(Text("Socks"))] })] }), MjSpacer(MjSpacer { attributes: Map({"height": "10px"}) })] }"#
+    ]];
+    let actual = view! {
+      <mj-section>
+        <Summary heading="Your order">
+          <mj-text>Socks</mj-text>
+        </Summary>
+        <Spacer />
+      </mj-section>
+    };
+    expected.assert_eq(&format!("{actual:?}"))
+}
+
+#[mrmx::component]
+fn Banner(
+    text: &str,
+    #[prop(into, default = "#ffffff".to_string())] color: String,
+) -> mrml::mj_section::MjSection {
+    view! {
+      <mj-section background-color={color}>
+        <mj-column>
+          <mj-text>{mrml::text::Text::from(text).into()}</mj-text>
+        </mj-column>
+      </mj-section>
+    }
+}
+
+#[test]
+fn component_macro() {
+    let expected = expect![[
+        r##"Fragment { children: [MjSection(MjSection { attributes: Map({"background-color": "#ffffff"}), children: [MjColumn(MjColumn { attributes: Map({}), children: [MjText(MjText { attributes: Map({}), children: [Text(Text("Sale"))] })] })] }), MjSection(MjSection { attributes: Map({"background-color": "red"}), children: [MjColumn(MjColumn { attributes: Map({}), children: [MjText(MjText { attributes: Map({}), children: [Text(Text("Sale"))] })] })] })] }"##
+    ]];
+    let actual: mrml::fragment::Fragment<MjBodyChild> = view! {
+      <Banner text="Sale" />
+      <Banner text="Sale" color="red" />
+    };
+    expected.assert_eq(&format!("{actual:?}"))
+}
+
+#[test]
+fn loops() {
+    let expected = expect![[
+        r#"MjColumn { attributes: Map({}), children: [MjText(MjText { attributes: Map({}), children: [Text(Text("Socks"))] }), MjText(MjText { attributes: Map({}), children: [Text(Text("Shoes"))] }), MjDivider(MjDivider { attributes: Map({}) })] }"#
+    ]];
+    let items = ["Socks", "Shoes"];
+    let extra: Vec<MjBodyChild> = vec![view! { <mj-divider /> }.into()];
+    let actual = view! {
+      <mj-column>
+        {for item in items {
+          <mj-text>{mrml::text::Text::from(item).into()}</mj-text>
+        }}
+        {..extra}
+      </mj-column>
+    };
+    expected.assert_eq(&format!("{actual:?}"))
+}
+
+#[test]
+fn explicit_into() {
+    let expected = expect![[
+        r#"MjText { attributes: Map({}), children: [Text(Text("Ships in 2 days")), Text(Text("Ships in 2 days")), Text(Text(" ")), Text(Text("Ships in 2 days")), Text(Text("!"))] }"#
+    ]];
+    let note = mrml::text::Text::from("Ships in 2 days");
+    let fallback = mrml::text::Text::from("Ships soon");
+    let fast = true;
+    let actual = view! {
+      <mj-text>
+        {note.clone().into()}
+        {if fast { note.clone().into() } else { fallback.into() }}
+        {Into::into(mrml::text::Text::from(" "))}
+        {let child: MjBodyChild = note.into(); child}
+        {"!"}
+      </mj-text>
+    };
+    expected.assert_eq(&format!("{actual:?}"))
+}
+
+#[test]
+fn root_loop() {
+    let expected = expect![[
+        r#"Fragment { children: [Node(Node { tag: "p", attributes: Map({}), children: [] }), Node(Node { tag: "p", attributes: Map({}), children: [] })] }"#
+    ]];
+    let actual: mrml::fragment::Fragment<MjBodyChild> = view! {
+      {for _ in 0..2 { <p></p> }}
+    };
+    expected.assert_eq(&format!("{actual:?}"))
+}
+
+#[test]
+fn conditionals() {
+    let expected = expect![[
+        r#"MjColumn { attributes: Map({}), children: [MjText(MjText { attributes: Map({}), children: [Text(Text("Shipped"))] }), MjDivider(MjDivider { attributes: Map({}) }), MjSpacer(MjSpacer { attributes: Map({}) })] }"#
+    ]];
+    enum Status {
+        Pending,
+        Shipped(bool),
+    }
+    let gift = false;
+    let status = if gift {
+        Status::Pending
+    } else {
+        Status::Shipped(true)
+    };
+    let actual = view! {
+      <mj-column>
+        {if let Status::Pending = status {
+          <mj-text>"Pending"</mj-text>
+        } else if gift {
+          <mj-text>"Gift"</mj-text>
+        } else {
+          <mj-text>"Shipped"</mj-text>
+        }}
+        {if !gift { <mj-divider /> }}
+        {match status {
+          Status::Pending => {}
+          Status::Shipped(tracked) if tracked => <mj-spacer />,
+          Status::Shipped(_) => { <mj-divider /> <mj-divider /> }
+        }}
+      </mj-column>
+    };
+    expected.assert_eq(&format!("{actual:?}"))
+}
+
+#[test]
+fn block_children() {
+    let expected = expect![[
+        r#"MjColumn { attributes: Map({}), children: [MjText(MjText { attributes: Map({}), children: [Text(Text("Socks"))] }), MjDivider(MjDivider { attributes: Map({}) }), MjSpacer(MjSpacer { attributes: Map({}) }), MjSpacer(MjSpacer { attributes: Map({}) })] }"#
+    ]];
+    let heading = mrml::text::Text::from("Socks");
+    let subtitle: Option<mrml::mj_text::MjText> = None;
+    let dividers = vec![view! { <mj-divider /> }];
+    let spacers: mrml::fragment::Fragment<MjBodyChild> = view! { <mj-spacer /> <mj-spacer /> };
+    let actual = view! {
+      <mj-column>
+        <mj-text>{heading}</mj-text>
+        {subtitle}
+        {dividers}
+        {spacers}
+      </mj-column>
+    };
+    expected.assert_eq(&format!("{actual:?}"))
+}
+
+#[test]
+fn optional_head() {
+    let expected = expect![[
+        r#"Mjml { attributes: MjmlAttributes { owa: None, lang: None, dir: None }, children: MjmlChildren { head: None, body: Some(MjBody { attributes: Map({}), children: [] }) } }"#
+    ]];
+    let head: Option<mrml::mj_head::MjHead> = None;
+    let actual = view! {
+      <mjml>
+        {head}
+        <mj-body></mj-body>
+      </mjml>
+    };
+    expected.assert_eq(&format!("{actual:?}"))
+}
+
+#[test]
+fn include() {
+    let expected = expect![[
+        r#"MjBody { attributes: Map({}), children: [MjInclude(MjIncludeBody { attributes: MjIncludeBodyAttributes { path: "tests/partials/footer.mjml", kind: Mjml }, children: [MjSection(MjSection { attributes: Map({}), children: [MjColumn(MjColumn { attributes: Map({}), children: [MjText(MjText { attributes: Map({}), children: [Text(Text("Unsubscribe"))] })] })] })] })] }"#
+    ]];
+    let actual = view! {
+      <mj-body>
+        <mj-include path="tests/partials/footer.mjml" />
+      </mj-body>
+    };
+    expected.assert_eq(&format!("{actual:?}"))
+}
+
+/// Partials included from a partial are resolved relative to it, and built
+/// into it.
+#[test]
+fn nested_include() {
+    let expected = expect![[
+        r#"MjBody { attributes: Map({}), children: [MjInclude(MjIncludeBody { attributes: MjIncludeBodyAttributes { path: "tests/partials/newsletter.mjml", kind: Mjml }, children: [MjSection(MjSection { attributes: Map({}), children: [MjColumn(MjColumn { attributes: Map({}), children: [MjText(MjText { attributes: Map({}), children: [Text(Text("News"))] })] })] }), MjSection(MjSection { attributes: Map({}), children: [MjColumn(MjColumn { attributes: Map({}), children: [MjText(MjText { attributes: Map({}), children: [Text(Text("Unsubscribe"))] })] })] })] })] }"#
+    ]];
+    let actual = view! {
+      <mj-body>
+        <mj-include path="tests/partials/newsletter.mjml" />
+      </mj-body>
+    };
+    expected.assert_eq(&format!("{actual:?}"))
+}
+
+#[test]
+fn include_html_and_css() {
+    let expected = expect![[
+        r#"Mjml { attributes: MjmlAttributes { owa: None, lang: None, dir: None }, children: MjmlChildren { head: Some(MjHead { children: [MjInclude(MjIncludeHead { attributes: MjIncludeHeadAttributes { path: "tests/partials/theme.css", kind: Css { inline: true } }, children: [Text(Text(".highlight { color: #ff6600; }\n"))] })] }), body: Some(MjBody { attributes: Map({}), children: [MjInclude(MjIncludeBody { attributes: MjIncludeBodyAttributes { path: "tests/partials/banner.html", kind: Html }, children: [MjRaw(MjRaw { children: [Text(Text("<div class=\"banner\">Sale</div>\n"))] })] })] }) } }"#
+    ]];
+    let actual = view! {
+      <mjml>
+        <mj-head>
+          <mj-include path="tests/partials/theme.css" type="css" css-inline="inline" />
+        </mj-head>
+        <mj-body>
+          <mj-include path="tests/partials/banner.html" type="html" />
+        </mj-body>
+      </mjml>
+    };
+    expected.assert_eq(&format!("{actual:?}"))
+}
+
+#[test]
+fn mjml_file() {
+    let expected = expect![[
+        r##"MjSection { attributes: Map({}), children: [MjColumn(MjColumn { attributes: Map({}), children: [Comment(Comment { children: "Greeting" }), MjText(MjText { attributes: Map({"color": "#ff6600"}), children: [Text(Text("Hello, Ada!"))] }), MjButton(MjButton { attributes: Map({"href": "https://example.com/users/7"}), children: [Text(Text("Your account"))] })] })] }"##
+    ]];
+    let name = "Ada";
+    let accent = "#ff6600";
+    let id = 7;
+    let actual = mrmx::mjml_file!("tests/templates/welcome.mjml");
+    expected.assert_eq(&format!("{actual:?}"))
+}
+
+#[test]
+fn mjml_file_document() {
+    struct Greeting<'a> {
+        name: &'a str,
+    }
+
+    impl std::fmt::Display for Greeting<'_> {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "Hello, {}!", self.name)
+        }
+    }
+
+    let expected = expect![[
+        r#"Mjml { attributes: MjmlAttributes { owa: None, lang: None, dir: None }, children: MjmlChildren { head: None, body: Some(MjBody { attributes: Map({}), children: [MjSection(MjSection { attributes: Map({}), children: [MjColumn(MjColumn { attributes: Map({}), children: [MjText(MjText { attributes: Map({}), children: [Text(Text("Total: 12.50"))] }), MjText(MjText { attributes: Map({}), children: [Text(Text("Hello, Ada!"))] })] })] })] }) } }"#
+    ]];
+    let name = "Ada";
+    let total = 12.5;
+    let actual = mrmx::mjml_file!("tests/templates/receipt.mjml");
+    expected.assert_eq(&format!("{actual:?}"))
+}
+
+#[test]
+fn render() {
+    use mrml::prelude::print::Printable;
+    use mrmx::Render;
+
+    let section = view! {
+      <mj-section>
+        <mj-column>
+          <mj-text>"Hello"</mj-text>
+        </mj-column>
+      </mj-section>
+    };
+    let expected =
+        expect!["<mj-section><mj-column><mj-text>Hello</mj-text></mj-column></mj-section>"];
+    expected.assert_eq(&section.to_mjml());
+
+    // mrml reads the printed document back as it is, and renders it the same.
+    let email = view! {
+      <mjml>
+        <mj-body>{section}</mj-body>
+      </mjml>
+    };
+    let mjml = email.to_mjml();
+    let parsed = mrml::parse(&mjml).unwrap().element;
+    assert_eq!(parsed.print_dense().unwrap(), mjml);
+    #[cfg(feature = "render")]
+    assert_eq!(
+        email.to_html(&Default::default()).unwrap(),
+        parsed.render(&Default::default()).unwrap(),
+    );
+}
+
+/// Renders a whole email with mrml, applying its `<mj-html-attributes>`.
+#[cfg(feature = "render")]
+#[test]
+fn render_html() {
+    use mrmx::Render;
+
+    let name = "Ada";
+    let email = view! {
+      <mjml>
+        <mj-head>
+          <mj-title>"Welcome"</mj-title>
+          <mj-html-attributes>
+            <mj-selector path="a.cta">
+              <mj-html-attribute name="data-id">"42"</mj-html-attribute>
+            </mj-selector>
+          </mj-html-attributes>
+        </mj-head>
+        <mj-body>
+          <mj-section>
+            <mj-column>
+              <mj-text>"Hello "{name}</mj-text>
+              <mj-raw>
+                <a class="cta" href="https://example.com/buy">"Buy"</a>
+              </mj-raw>
+            </mj-column>
+          </mj-section>
+        </mj-body>
+      </mjml>
+    };
+    let html = email.to_html(&Default::default()).unwrap();
+    assert!(html.contains("Hello Ada"), "{html}");
+    assert!(
+        html.contains(r#"<a class="cta" href="https://example.com/buy" data-id="42">Buy</a>"#),
+        "{html}"
+    );
+}
+
+/// Every element mrml models must work in `view!`. The matches are exhaustive,
+/// so this stops compiling when mrml gains an element.
+#[test]
+fn element_coverage() {
+    use mrml::mj_accordion::MjAccordionChild;
+    use mrml::mj_accordion_element::{MjAccordionElement, MjAccordionElementChildren};
+    use mrml::mj_attributes::MjAttributesChild;
+    use mrml::mj_carousel::MjCarouselChild;
+    use mrml::mj_head::MjHeadChild;
+    use mrml::mj_navbar::MjNavbarChild;
+    use mrml::mj_raw::MjRawChild;
+    use mrml::mj_social::MjSocialChild;
+    use mrml::mjml::{Mjml, MjmlChildren};
+    use mrmx::{Render, TryWithAttribute, WithAttribute, WithChildren};
+
+    fn element<T: WithAttribute + TryWithAttribute + Render>(_: &T) {}
+    fn container<T: WithAttribute + TryWithAttribute + Render + WithChildren>(_: &T) {}
+
+    fn mjml(mjml: &Mjml) {
+        container(mjml);
+        let MjmlChildren { head, body } = &mjml.children;
+        if let Some(head) = head {
+            container(head);
+            head.children.iter().for_each(head_child);
+        }
+        if let Some(body) = body {
+            container(body);
+            body.children.iter().for_each(body_child);
+        }
+    }
+
+    fn head_child(child: &MjHeadChild) {
+        match child {
+            MjHeadChild::Comment(_) => {}
+            MjHeadChild::MjAttributes(el) => {
+                container(el);
+                for child in &el.children {
+                    match child {
+                        MjAttributesChild::MjAttributesAll(el) => element(el),
+                        MjAttributesChild::MjAttributesClass(el) => element(el),
+                        MjAttributesChild::MjAttributesElement(el) => element(el),
+                    }
+                }
+            }
+            MjHeadChild::MjBreakpoint(el) => element(el),
+            MjHeadChild::MjFont(el) => element(el),
+            MjHeadChild::MjInclude(el) => container(el),
+            MjHeadChild::MjPreview(el) => container(el),
+            MjHeadChild::MjRaw(el) => {
+                container(el);
+                el.children.iter().for_each(raw_child);
+            }
+            MjHeadChild::MjStyle(el) => container(el),
+            MjHeadChild::MjTitle(el) => container(el),
+        }
+    }
+
+    fn body_child(child: &MjBodyChild) {
+        match child {
+            MjBodyChild::Comment(_) | MjBodyChild::Text(_) => {}
+            MjBodyChild::MjAccordion(el) => {
+                container(el);
+                for child in &el.children {
+                    match child {
+                        MjAccordionChild::Comment(_) => {}
+                        MjAccordionChild::MjAccordionElement(el) => accordion_element(el),
+                    }
+                }
+            }
+            MjBodyChild::MjButton(el) => container(el),
+            MjBodyChild::MjCarousel(el) => {
+                container(el);
+                for child in &el.children {
+                    match child {
+                        MjCarouselChild::Comment(_) => {}
+                        MjCarouselChild::MjCarouselImage(el) => element(el),
+                    }
+                }
+            }
+            MjBodyChild::MjColumn(el) => container(el),
+            MjBodyChild::MjDivider(el) => element(el),
+            MjBodyChild::MjGroup(el) => container(el),
+            MjBodyChild::MjHero(el) => container(el),
+            MjBodyChild::MjInclude(el) => container(el),
+            MjBodyChild::MjImage(el) => element(el),
+            MjBodyChild::MjNavbar(el) => {
+                container(el);
+                for child in &el.children {
+                    match child {
+                        MjNavbarChild::Comment(_) => {}
+                        MjNavbarChild::MjNavbarLink(el) => {
+                            container(el);
+                            el.children.iter().for_each(raw_child);
+                        }
+                    }
+                }
+            }
+            MjBodyChild::MjRaw(el) => {
+                container(el);
+                el.children.iter().for_each(raw_child);
+            }
+            MjBodyChild::MjSection(el) => container(el),
+            MjBodyChild::MjSocial(el) => {
+                container(el);
+                for child in &el.children {
+                    match child {
+                        MjSocialChild::Comment(_) => {}
+                        MjSocialChild::MjSocialElement(el) => container(el),
+                    }
+                }
+            }
+            MjBodyChild::MjSpacer(el) => element(el),
+            MjBodyChild::MjTable(el) => container(el),
+            MjBodyChild::MjText(el) => container(el),
+            MjBodyChild::MjWrapper(el) => container(el),
+            MjBodyChild::Node(el) => container(el),
+        }
+    }
+
+    fn accordion_element(el: &MjAccordionElement) {
+        container(el);
+        let MjAccordionElementChildren { title, text } = &el.children;
+        if let Some(title) = title {
+            container(title);
+        }
+        if let Some(text) = text {
+            container(text);
+            text.children.iter().for_each(raw_child);
+        }
+    }
+
+    fn raw_child(child: &MjRawChild) {
+        match child {
+            MjRawChild::Comment(_) | MjRawChild::Text(_) => {}
+            MjRawChild::Node(el) => container(el),
+        }
+    }
+
+    mjml(&Mjml::default());
+    container(&mrmx::html_attributes::MjHtmlAttributes::default());
+    container(&mrmx::html_attributes::MjSelector::default());
+    container(&mrmx::html_attributes::MjHtmlAttribute::default());
+}
+
+#[test]
+fn html_attributes() {
+    use mrmx::html_attributes::MjHtmlAttributes;
+    use mrmx::Render;
+
+    let attributes = view! {
+      <mj-html-attributes>
+        <mj-selector path=".cta a, #footer > img">
+          <mj-html-attribute name="data-id">{42.to_string()}</mj-html-attribute>
+          <mj-html-attribute name="aria-hidden">"true"</mj-html-attribute>
+        </mj-selector>
+      </mj-html-attributes>
+    };
+    let expected = expect![[
+        r#"<mj-html-attributes><mj-selector path=".cta a, #footer > img"><mj-html-attribute name="data-id">42</mj-html-attribute><mj-html-attribute name="aria-hidden">true</mj-html-attribute></mj-selector></mj-html-attributes>"#
+    ]];
+    expected.assert_eq(&attributes.to_mjml());
+
+    let email: mrml::mjml::Mjml = view! {
+      <mjml lang="en">
+        <mj-head>
+          <mj-title>"Sale"</mj-title>
+          <mj-html-attributes>
+            <mj-selector path=".cta a">
+              <mj-html-attribute name="data-id">"42"</mj-html-attribute>
+            </mj-selector>
+          </mj-html-attributes>
+        </mj-head>
+        <mj-body></mj-body>
+      </mjml>
+    };
+    let head = email.children.head.as_ref().unwrap();
+    assert_eq!(MjHtmlAttributes::in_head(head).len(), 1);
+    let expected = expect![[
+        r#"<mjml lang="en"><mj-head><mj-title>Sale</mj-title><mj-html-attributes><mj-selector path=".cta a"><mj-html-attribute name="data-id">42</mj-html-attribute></mj-selector></mj-html-attributes></mj-head><mj-body /></mjml>"#
+    ]];
+    expected.assert_eq(&email.to_mjml());
+
+    // It can be built apart from the head, like in a component.
+    let head = view! {
+      <mj-head>
+        {attributes}
+        <mj-html-attributes></mj-html-attributes>
+      </mj-head>
+    };
+    assert_eq!(MjHtmlAttributes::in_head(&head).len(), 2);
+    let expected = expect![[
+        r#"<mj-head><mj-html-attributes><mj-selector path=".cta a, #footer > img"><mj-html-attribute name="data-id">42</mj-html-attribute><mj-html-attribute name="aria-hidden">true</mj-html-attribute></mj-selector></mj-html-attributes><mj-html-attributes /></mj-head>"#
+    ]];
+    expected.assert_eq(&head.to_mjml());
+}
+
+#[cfg(feature = "render")]
+#[test]
+fn apply_html_attributes() {
+    let attributes = view! {
+      <mj-html-attributes>
+        <mj-selector path=".cta a, #footer > img">
+          <mj-html-attribute name="data-id">"42"</mj-html-attribute>
+          <mj-html-attribute name="title">"Say \"hi\""</mj-html-attribute>
+        </mj-selector>
+      </mj-html-attributes>
+    };
+    let html = r#"<div class="cta big"><a href="/">Buy</a></div><!--[if mso]><a><![endif]--><div id="footer"><p><img src="a.png" /></p><img src="b.png"></div>"#;
+    let expected = expect![[
+        r#"<div class="cta big"><a href="/" data-id="42" title="Say &quot;hi&quot;">Buy</a></div><!--[if mso]><a><![endif]--><div id="footer"><p><img src="a.png" /></p><img src="b.png" data-id="42" title="Say &quot;hi&quot;"></div>"#
+    ]];
+    expected.assert_eq(&attributes.apply(html).unwrap());
+
+    let siblings = view! {
+      <mj-html-attributes>
+        <mj-selector path="h1 + p">
+          <mj-html-attribute name="class">"lead"</mj-html-attribute>
+        </mj-selector>
+      </mj-html-attributes>
+    };
+    let expected = expect!["invalid selector `h1 + p`: Unsupported combinator `+` in selector."];
+    expected.assert_eq(&siblings.apply(html).unwrap_err().to_string());
+}
+
+#[test]
+fn url_policy() {
+    use mrmx::url_policy::UrlPolicy;
+    use mrmx::Render;
+
+    let check = |node: &dyn Render, policy: &UrlPolicy| match node.check_urls(policy) {
+        Ok(()) => "ok".to_string(),
+        Err(err) => err.to_string(),
+    };
+    let strict = UrlPolicy::default();
+    let loose = UrlPolicy {
+        schemes: vec!["mailto".to_string()],
+        allow_relative: true,
+        allow_private_hosts: true,
+    };
+
+    let links = [
+        "https://example.com/",
+        "/unsubscribe",
+        "mailto:help@example.com",
+    ];
+    let columns =
+        links.map(|link| view! { <mj-column><mj-button href={link}>"Go"</mj-button></mj-column> });
+    let hosts = [
+        "https://staging.internal/",
+        "https://[::1]:8080/",
+        "https://172.20.0.1/",
+    ];
+    let images = hosts.map(|host| view! { <mj-image src={host} /> });
+    let obfuscated = view! { <mj-text>{mrmx::raw("<a href=\"java&#x09;script&colon;alert(1)\">Hi</a>")}</mj-text> };
+    let attributes = view! {
+      <mj-html-attributes>
+        <mj-selector path="a">
+          <mj-html-attribute name="href">"data:text/html,hi"</mj-html-attribute>
+        </mj-selector>
+      </mj-html-attributes>
+    };
+
+    let nodes = columns.iter().map(|column| column as &dyn Render);
+    let nodes = nodes.chain(images.iter().map(|image| image as &dyn Render));
+    let mut results = nodes
+        .map(|node| format!("{} / {}", check(node, &strict), check(node, &loose)))
+        .collect::<Vec<_>>();
+    results.push(check(&obfuscated, &loose));
+    results.push(check(&attributes, &loose));
+
+    let contact = view! {
+      #![allowed_urls("mailto:", "relative")]
+      <mj-column>
+        <mj-button href="mailto:help@example.com">"Contact"</mj-button>
+        <mj-button href="/unsubscribe">"Unsubscribe"</mj-button>
+      </mj-column>
+    };
+    results.push(check(&contact, &strict));
+    results.push(check(&contact, &UrlPolicy::allowing("mailto:, relative")));
+
+    let expected = expect![[r#"
+        ok / ok
+        unsafe `href` on <mj-button>: relative URLs are not allowed / ok
+        unsafe `href` on <mj-button>: `mailto:` URLs are not allowed / ok
+        unsafe `src` on <mj-image>: `staging.internal` is a local or private host / ok
+        unsafe `src` on <mj-image>: `::1` is a local or private host / ok
+        unsafe `src` on <mj-image>: `172.20.0.1` is a local or private host / ok
+        unsafe `href` on <a>: `javascript:` URLs are not allowed
+        unsafe `href` on <mj-html-attribute>: `data:` URLs are not allowed
+        unsafe `href` on <mj-button>: `mailto:` URLs are not allowed
+        ok"#]];
+    expected.assert_eq(&results.join("\n"));
+}
+
+#[cfg(feature = "render")]
+#[test]
+fn render_checks_urls() {
+    use mrmx::url_policy::UrlPolicy;
+    use mrmx::{Render, RenderError};
+
+    let url = "http://localhost:3000/confirm";
+    let email = view! {
+      <mjml>
+        <mj-body>
+          <mj-button href={url}>"Confirm"</mj-button>
+        </mj-body>
+      </mjml>
+    };
+    let options = Default::default();
+    let err = email.to_html(&options).unwrap_err();
+    assert!(matches!(err, RenderError::UnsafeUrl(_)));
+    let expected = expect!["unsafe `href` on <mj-button>: `http:` URLs are not allowed"];
+    expected.assert_eq(&err.to_string());
+
+    let policy = UrlPolicy::allowing("http:, private-hosts");
+    assert!(email.to_html_with(&options, &policy).is_ok());
+
+    // The example of `mrmx::url_policy`, which `to_html` accepts as it is.
+    let contact = view! {
+      #![allowed_urls("mailto:")]
+      <mj-button href="mailto:help@example.com">"Contact us"</mj-button>
+    };
+    let html = contact.to_html(&options).unwrap();
+    assert!(html.contains("mailto:help@example.com"), "{html}");
+    let unsubscribe = view! {
+      #![allowed_urls("relative")]
+      <mj-button href="/unsubscribe">"Unsubscribe"</mj-button>
+    };
+    assert!(unsubscribe.to_html(&options).is_ok());
+}
@@ -0,0 +1,6 @@
+/// The compile errors of the macros, in `tests/ui`. Run with
+/// `TRYBUILD=overwrite` to update the expected `.stderr` files.
+#[test]
+fn ui() {
+    trybuild::TestCases::new().compile_fail("tests/ui/*.rs");
+}
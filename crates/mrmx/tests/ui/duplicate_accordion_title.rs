@@ -0,0 +1,11 @@
+use mrmx::view;
+
+fn main() {
+    let _ = view! {
+      <mj-accordion-element>
+        <mj-accordion-title>"Shipping"</mj-accordion-title>
+        <mj-accordion-title>"Returns"</mj-accordion-title>
+        <mj-accordion-text>"Free over $50."</mj-accordion-text>
+      </mj-accordion-element>
+    };
+}
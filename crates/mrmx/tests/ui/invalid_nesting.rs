@@ -0,0 +1,9 @@
+use mrmx::view;
+
+fn main() {
+    let _ = view! {
+      <mj-head>
+        <mj-section />
+      </mj-head>
+    };
+}
@@ -0,0 +1,7 @@
+use mrmx::view;
+
+fn main() {
+    let _ = view! {
+      <mj-section background-color="not-a-color" padding="10px 20px 30px 40px 50px" />
+    };
+}
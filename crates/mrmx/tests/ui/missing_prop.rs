@@ -0,0 +1,14 @@
+use mrmx::view;
+
+#[mrmx::component]
+fn Banner(text: &str) -> mrml::mj_text::MjText {
+    view! { <mj-text>{text}</mj-text> }
+}
+
+fn main() {
+    let _: mrml::mj_column::MjColumn = view! {
+      <mj-column>
+        <Banner />
+      </mj-column>
+    };
+}
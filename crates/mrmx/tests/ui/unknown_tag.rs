@@ -0,0 +1,7 @@
+use mrmx::view;
+
+fn main() {
+    let _ = view! {
+      <mj-colum />
+    };
+}
@@ -0,0 +1,7 @@
+use mrmx::view;
+
+fn main() {
+    let _ = view! {
+      <mj-button href="javascript:alert(1)">"Buy"</mj-button>
+    };
+}